@@ -14,9 +14,12 @@ fun! s:main() abort
     let s:editor_data = {'locations': []}
     let s:locations_texts = {}
     let s:location_index = v:null
+    let s:labels_namespace = nvim_create_namespace('cargo-limit')
+    let s:labeled_buffers = {}
     let s:workspace_root = v:null
     let s:temp_dir = v:null
     let s:deprecated_cargo_limit_open = v:null
+    let s:handlers_are_set_up = v:false
     let s:lazyredraw = &lazyredraw
     let s:allow_redraw = v:true
     call jobstart(['cargo', 'metadata', '--quiet', '--format-version=1'], {
@@ -31,6 +34,9 @@ fun! s:main() abort
 endf
 
 fun! s:on_cargo_metadata(_job_id, data, event) abort
+  " Even outside of workspace: cargo-limit may be run in :terminal, then it uses $NVIM
+  call s:maybe_setup_handlers()
+
   if a:event ==# 'stdout'
     let l:stdout = trim(join(a:data, ''))
     if !empty(l:stdout)
@@ -50,13 +56,20 @@ endf
 fun! s:start_server(escaped_workspace_root) abort
   const TEMP_DIR_PREFIX = 'nvim-cargo-limit-'
 
+  " Every instance has its own address, so all of them are updated by cargo-limit
+  let l:instance_postfix = a:escaped_workspace_root . '-' . getpid()
   if has('unix')
-    let s:temp_dir = '/tmp/' . TEMP_DIR_PREFIX . $USER
-    let l:server_address = s:temp_dir . '/' . a:escaped_workspace_root
+    let s:temp_dir = s:sockets_dir()
+    call s:maybe_create_temp_dir()
+    if !s:is_private_dir(s:temp_dir)
+      call s:log_error('not trusted, must be owned by current user and have 0700 permissions:', s:temp_dir)
+      return
+    end
+    let l:server_address = s:temp_dir . '/' . l:instance_postfix
     call s:maybe_delete_dead_unix_socket(l:server_address)
   elseif has('win32')
     " TODO: limit to 256 characters? bytes?
-    let l:server_address_postfix = TEMP_DIR_PREFIX . $USERNAME . '-' . a:escaped_workspace_root
+    let l:server_address_postfix = TEMP_DIR_PREFIX . $USERNAME . '-' . l:instance_postfix
     let l:server_address = '\\.\pipe\' . l:server_address_postfix
   else
     throw 'unsupported OS'
@@ -64,17 +77,22 @@ fun! s:start_server(escaped_workspace_root) abort
 
   if !filereadable(l:server_address)
     call s:maybe_create_temp_dir()
-    call s:maybe_setup_handlers()
     call serverstart(l:server_address)
     call s:log_info('ready')
   end
 endf
 
 fun! s:maybe_setup_handlers() abort
+  if s:handlers_are_set_up
+    return
+  end
+  let s:handlers_are_set_up = v:true
+
   augroup CargoLimitAutocommands
     autocmd!
     autocmd VimLeavePre * call s:maybe_create_temp_dir()
     autocmd BufWritePost *.rs call s:on_buffer_write(expand('<afile>:p'))
+    autocmd BufWinEnter *.rs call s:show_labels(expand('<afile>:p'))
   augroup END
 
   if exists('*CargoLimitOpen')
@@ -115,6 +133,12 @@ fun! s:maybe_setup_handlers() abort
 
     call g:CargoLimitUpdate(s:editor_data)
     call s:finalize_locations()
+    call s:clear_labels()
+    for l:location in s:editor_data.locations
+      for l:span in [l:location] + get(l:location, 'related', [])
+        call s:show_labels(l:span.path)
+      endfor
+    endfor
   endf
 
   fun! g:CargoLimitOpenNextLocation() abort
@@ -124,6 +148,43 @@ fun! s:maybe_setup_handlers() abort
   fun! g:CargoLimitOpenPrevLocation() abort
     call s:switch_location(function('s:decrement_location_index'))
   endf
+
+  fun! g:CargoLimitOpenRelatedLocations() abort
+    if empty(s:editor_data.locations) || s:location_index is# v:null
+      return
+    end
+    let l:location = s:current_location()
+    let l:items = [{'filename': l:location.path, 'lnum': l:location.line, 'col': l:location.column, 'text': l:location.message}]
+    for l:related in get(l:location, 'related', [])
+      call add(l:items, {'filename': l:related.path, 'lnum': l:related.line, 'col': l:related.column, 'text': s:label(l:related)})
+    endfor
+    call setloclist(0, l:items)
+    lopen
+  endf
+
+  fun! g:CargoLimitApplyFix() abort
+    if empty(s:editor_data.locations) || s:location_index is# v:null
+      return
+    end
+    let l:location = s:current_location()
+    let l:fixes = get(l:location, 'fixes', [])
+    if empty(l:fixes) || s:is_current_location_edited()
+      call s:log_info('no fixes for current location')
+      return
+    end
+
+    let l:fix = l:fixes[0]
+    if len(l:fixes) ># 1
+      let l:choice = inputlist(['Select fix:'] + map(copy(l:fixes), { i, fix -> (i + 1) . '. ' . fix.message }))
+      if l:choice <# 1 || l:choice ># len(l:fixes)
+        return
+      end
+      let l:fix = l:fixes[l:choice - 1]
+    end
+    call s:apply_replacements(l:location, l:fix.replacements)
+    let l:location.fixes = []
+    call s:log_info('applied fix:', l:fix.message)
+  endf
 endf
 
 fun! s:downgrade_editor_data_format() abort
@@ -262,10 +323,68 @@ fun! s:finalize_locations() abort
     if l:text isnot# v:null
       let s:locations_texts[l:index] = l:text
     end
+    let s:editor_data.locations[l:index].original_line = l:location.line
   endfor
   let s:location_index = 0
 endf
 
+fun! s:apply_replacements(location, replacements) abort
+  " Replacing from the end keeps positions of the rest valid
+  let l:replacements = sort(copy(a:replacements), { a, b -> a.line_start ==# b.line_start ? b.column_start - a.column_start : b.line_start - a.line_start })
+  for l:replacement in l:replacements
+    let l:buf = bufnr(l:replacement.path, v:true)
+    call bufload(l:buf)
+    " Lines of the location could be shifted since the build
+    let l:shift = l:replacement.path ==# a:location.path ? a:location.line - a:location.original_line : 0
+    let l:start = s:byte_position(l:buf, l:replacement.line_start + l:shift, l:replacement.column_start)
+    let l:end = s:byte_position(l:buf, l:replacement.line_end + l:shift, l:replacement.column_end)
+    call nvim_buf_set_text(l:buf, l:start[0], l:start[1], l:end[0], l:end[1], split(l:replacement.text, "\n", v:true))
+  endfor
+endf
+
+fun! s:byte_position(buf, line, column) abort
+  let l:line_count = nvim_buf_line_count(a:buf)
+  let l:line = min([a:line, l:line_count])
+  let l:text = nvim_buf_get_lines(a:buf, l:line - 1, l:line, v:true)[0]
+  let l:byte = a:line ># l:line_count ? -1 : byteidx(l:text, a:column - 1)
+  return [l:line - 1, l:byte <# 0 ? len(l:text) : l:byte]
+endf
+
+fun! s:show_labels(path) abort
+  let l:buf = bufnr(a:path)
+  let l:bufinfo = s:bufinfo_if_loaded(l:buf)
+  if empty(l:bufinfo) || has_key(s:labeled_buffers, l:buf)
+    return
+  end
+  let s:labeled_buffers[l:buf] = v:true
+
+  for l:location in s:editor_data.locations
+    for l:span in [l:location] + get(l:location, 'related', [])
+      let l:label = s:label(l:span)
+      if l:span.path ==# a:path && !empty(l:label) && l:span.line <=# l:bufinfo.linecount
+        call nvim_buf_set_extmark(l:buf, s:labels_namespace, l:span.line - 1, 0, {
+          \ 'virt_text': [[l:label, 'Comment']],
+          \ 'virt_text_pos': 'eol',
+          \ })
+      end
+    endfor
+  endfor
+endf
+
+fun! s:clear_labels() abort
+  for l:buf in keys(s:labeled_buffers)
+    if bufexists(str2nr(l:buf))
+      call nvim_buf_clear_namespace(str2nr(l:buf), s:labels_namespace, 0, -1)
+    end
+  endfor
+  let s:labeled_buffers = {}
+endf
+
+fun! s:label(span) abort
+  let l:label = get(a:span, 'label', v:null)
+  return l:label is# v:null ? '' : l:label
+endf
+
 fun! s:switch_location(change_location_index) abort
   echomsg ''
   let l:current_file = s:current_file()
@@ -388,6 +507,25 @@ fun! s:maybe_delete_dead_unix_socket(server_address) abort
   end
 endf
 
+" The same as in _cargo-limit-open-in-nvim
+fun! s:sockets_dir() abort
+  const SOCKETS_DIR_NAME = 'cargo-limit'
+  if $XDG_RUNTIME_DIR =~# '^/'
+    return $XDG_RUNTIME_DIR . '/' . SOCKETS_DIR_NAME
+  end
+  return '/tmp/' . SOCKETS_DIR_NAME . '-' . luaeval('vim.loop.getuid()')
+endf
+
+fun! s:is_private_dir(path) abort
+  return luaeval(
+    \ '(function(path)
+    \   local stat = vim.loop.fs_lstat(path)
+    \   return stat ~= nil and stat.type == "directory" and stat.uid == vim.loop.getuid()
+    \     and bit.band(stat.mode, tonumber("077", 8)) == 0
+    \ end)(_A)',
+    \ a:path)
+endf
+
 fun! s:maybe_create_temp_dir() abort
   if s:temp_dir isnot# v:null
     call mkdir(s:temp_dir, 'p', 0700)
@@ -0,0 +1,253 @@
+use crate::{
+    env_vars,
+    io::Buffers,
+    models::{Level, Location},
+};
+use anyhow::{Context, Result};
+use cargo_metadata::CompilerMessage;
+use serde_json::json;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const RUSTC: &str = "rustc";
+const GITLAB_REPORT_FILE_NAME: &str = "gl-code-quality-report.json";
+
+/// GitHub Actions turn `::error file=...` lines of stdout into inline annotations.
+pub fn github_actions_detected() -> bool {
+    env::var_os(env_vars::GITHUB_ACTIONS).is_some()
+}
+
+/// GitLab CI picks up the report from `artifacts:reports:codequality`.
+pub fn default_gitlab_report() -> Option<PathBuf> {
+    env::var_os(env_vars::GITLAB_CI).map(|_| PathBuf::from(GITLAB_REPORT_FILE_NAME))
+}
+
+pub fn write_github_annotations(
+    buffers: &mut Buffers,
+    messages: &[(CompilerMessage, Location)],
+    workspace_root: &Path,
+) -> Result<()> {
+    for (_, location) in messages {
+        buffers.writeln_to_stdout(&github_annotation(location, workspace_root))?;
+    }
+    Ok(())
+}
+
+pub fn write_gitlab_report(
+    path: &Path,
+    messages: &[(CompilerMessage, Location)],
+    workspace_root: &Path,
+) -> Result<()> {
+    let report = messages
+        .iter()
+        .map(|(message, location)| gitlab_issue(message, location, workspace_root))
+        .collect::<Vec<_>>();
+    fs::write(path, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn github_annotation(location: &Location, workspace_root: &Path) -> String {
+    let command = match location.level {
+        Level::Ice | Level::Error | Level::TestFailure => "error",
+        Level::Warning => "warning",
+        _ => "notice",
+    };
+    format!(
+        "::{command} file={},line={},col={}::{}",
+        escape_github_property(&relative_path(location, workspace_root)),
+        location.line,
+        location.column,
+        escape_github_data(&location.message),
+    )
+}
+
+fn gitlab_issue(
+    message: &CompilerMessage,
+    location: &Location,
+    workspace_root: &Path,
+) -> serde_json::Value {
+    let severity = match location.level {
+        Level::Ice => "blocker",
+        Level::Error | Level::TestFailure => "critical",
+        Level::Warning => "minor",
+        _ => "info",
+    };
+    let check_name = message
+        .message
+        .code
+        .as_ref()
+        .map(|code| code.code.as_str())
+        .unwrap_or(RUSTC);
+    let path = relative_path(location, workspace_root);
+    let fingerprint = fingerprint(&[
+        check_name,
+        &path,
+        &location.line.to_string(),
+        &location.column.to_string(),
+        &location.message,
+    ]);
+    json!({
+        "description": location.message,
+        "check_name": check_name,
+        "fingerprint": fingerprint,
+        "severity": severity,
+        "location": {
+            "path": path,
+            "lines": { "begin": location.line },
+        },
+    })
+}
+
+fn relative_path(location: &Location, workspace_root: &Path) -> String {
+    location
+        .path
+        .strip_prefix(workspace_root)
+        .unwrap_or(&location.path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn fingerprint(fields: &[&str]) -> String {
+    // FNV-1a, which is stable across runs and toolchains unlike std hashers
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    let hash = fields
+        .iter()
+        .flat_map(|field| field.bytes().chain([0]))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+    format!("{hash:016x}")
+}
+
+fn escape_github_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_github_property(text: &str) -> String {
+    escape_github_data(text)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    const WORKSPACE_ROOT: &str = "/workspace";
+
+    fn compiler_message(code: Option<&str>) -> CompilerMessage {
+        serde_json::from_value(json!({
+            "package_id": "app 0.1.0 (path+file:///workspace)",
+            "manifest_path": "/workspace/Cargo.toml",
+            "target": {
+                "name": "app",
+                "kind": ["bin"],
+                "crate_types": ["bin"],
+                "src_path": "/workspace/src/main.rs",
+                "edition": "2024",
+                "doctest": false,
+                "test": true,
+                "doc": true,
+            },
+            "message": {
+                "message": "unused variable: `x`",
+                "code": code.map(|code| json!({ "code": code, "explanation": null })),
+                "level": "warning",
+                "spans": [],
+                "children": [],
+                "rendered": null,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn location(level: Level, message: &str) -> Location {
+        Location {
+            path: PathBuf::from("/workspace/src/a,b:c.rs"),
+            line: 3,
+            column: 9,
+            message: message.to_owned(),
+            level,
+            label: None,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn github_annotations() {
+        let workspace_root = Path::new(WORKSPACE_ROOT);
+        assert_eq!(
+            github_annotation(
+                &location(Level::Error, "100% broken\r\nsee: a, b"),
+                workspace_root
+            ),
+            "::error file=src/a%2Cb%3Ac.rs,line=3,col=9::100%25 broken%0D%0Asee: a, b"
+        );
+        assert_eq!(
+            github_annotation(&location(Level::Warning, "unused"), workspace_root),
+            "::warning file=src/a%2Cb%3Ac.rs,line=3,col=9::unused"
+        );
+        assert_eq!(
+            github_annotation(&location(Level::Note, "note"), Path::new("/other")),
+            "::notice file=/workspace/src/a%2Cb%3Ac.rs,line=3,col=9::note"
+        );
+    }
+
+    #[test]
+    fn gitlab_fingerprint() {
+        assert_eq!(fingerprint(&[]), "cbf29ce484222325");
+        assert_eq!(fingerprint(&["a"]), fingerprint(&["a"]));
+        assert_ne!(fingerprint(&["ab", "c"]), fingerprint(&["a", "bc"]));
+        assert_ne!(fingerprint(&["a", "b"]), fingerprint(&["b", "a"]));
+    }
+
+    #[test]
+    fn gitlab_issues() {
+        let workspace_root = Path::new(WORKSPACE_ROOT);
+        let warning = location(Level::Warning, "unused variable: `x`");
+        let error = location(Level::Ice, "internal compiler error");
+        let fingerprint_of = |check_name, location: &Location| {
+            fingerprint(&[check_name, "src/a,b:c.rs", "3", "9", &location.message])
+        };
+        assert_eq!(
+            vec![
+                gitlab_issue(
+                    &compiler_message(Some("unused_variables")),
+                    &warning,
+                    workspace_root
+                ),
+                gitlab_issue(&compiler_message(None), &error, workspace_root),
+            ],
+            vec![
+                json!({
+                    "description": "unused variable: `x`",
+                    "check_name": "unused_variables",
+                    "fingerprint": fingerprint_of("unused_variables", &warning),
+                    "severity": "minor",
+                    "location": {
+                        "path": "src/a,b:c.rs",
+                        "lines": { "begin": 3 },
+                    },
+                }),
+                json!({
+                    "description": "internal compiler error",
+                    "check_name": "rustc",
+                    "fingerprint": fingerprint_of("rustc", &error),
+                    "severity": "blocker",
+                    "location": {
+                        "path": "src/a,b:c.rs",
+                        "lines": { "begin": 3 },
+                    },
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{models::Location, options::Options};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const BASELINE_FILE_NAME: &str = ".cargo-limit-baseline.json";
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    warnings: Vec<Fingerprint>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(deny_unknown_fields)]
+struct Fingerprint {
+    code: Option<String>,
+    path: String,
+    line: String,
+}
+
+#[derive(Default)]
+struct SourceLines {
+    files: HashMap<PathBuf, Vec<String>>,
+}
+
+impl Baseline {
+    pub fn load(options: &Options, workspace_root: &Path) -> Result<Self> {
+        let Some(path) = Self::path(options, workspace_root) else {
+            return Ok(Self::default());
+        };
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)?;
+        serde_json::from_str(&text).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Warnings are pairs of lint code (if any) and location.
+    pub fn new<'w>(
+        warnings: impl IntoIterator<Item = (Option<&'w str>, &'w Location)>,
+        workspace_root: &Path,
+    ) -> Self {
+        let mut source_lines = SourceLines::default();
+        let mut warnings = warnings
+            .into_iter()
+            .map(|(code, location)| {
+                Fingerprint::new(code, location, workspace_root, &mut source_lines)
+            })
+            .collect::<Vec<_>>();
+        warnings.sort();
+        Self { warnings }
+    }
+
+    pub fn save(&self, options: &Options, workspace_root: &Path) -> Result<PathBuf> {
+        let path = Self::path(options, workspace_root).context("baseline file is not set")?;
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(&path, format!("{text}\n"))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.warnings.len()
+    }
+
+    /// Skips warnings that are in the baseline, `key` gives lint code (if any) and location.
+    pub fn filter<T>(
+        &self,
+        warnings: Vec<T>,
+        workspace_root: &Path,
+        key: impl Fn(&T) -> (Option<&str>, &Location),
+    ) -> Vec<T> {
+        if self.warnings.is_empty() {
+            return warnings;
+        }
+
+        let mut remaining = HashMap::<&Fingerprint, usize>::new();
+        for i in &self.warnings {
+            *remaining.entry(i).or_default() += 1;
+        }
+
+        let mut source_lines = SourceLines::default();
+        warnings
+            .into_iter()
+            .filter(|warning| {
+                let (code, location) = key(warning);
+                let fingerprint =
+                    Fingerprint::new(code, location, workspace_root, &mut source_lines);
+                match remaining.get_mut(&fingerprint) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    },
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+
+    fn path(options: &Options, workspace_root: &Path) -> Option<PathBuf> {
+        options
+            .baseline_file
+            .as_ref()
+            .map(|path| workspace_root.join(path))
+    }
+}
+
+impl Fingerprint {
+    fn new(
+        code: Option<&str>,
+        location: &Location,
+        workspace_root: &Path,
+        source_lines: &mut SourceLines,
+    ) -> Self {
+        let path = location
+            .path
+            .strip_prefix(workspace_root)
+            .unwrap_or(&location.path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        Self {
+            code: code.map(ToOwned::to_owned),
+            path,
+            line: source_lines.normalized_line(&location.path, location.line),
+        }
+    }
+}
+
+impl SourceLines {
+    fn normalized_line(&mut self, path: &Path, line: usize) -> String {
+        let lines = self.files.entry(path.to_path_buf()).or_insert_with(|| {
+            fs::read_to_string(path)
+                .map(|text| text.lines().map(ToOwned::to_owned).collect())
+                .unwrap_or_default()
+        });
+        lines
+            .get(line.saturating_sub(1))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Level;
+    use pretty_assertions::assert_eq;
+    use std::env;
+
+    fn warning(path: &Path, line: usize) -> Location {
+        Location {
+            path: path.to_owned(),
+            line,
+            column: 1,
+            message: "unused variable".to_owned(),
+            level: Level::Warning,
+            label: None,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fingerprint_filter_save_and_load() -> Result<()> {
+        let workspace_root =
+            env::temp_dir().join(format!("cargo-limit-baseline-{}", std::process::id()));
+        fs::create_dir_all(workspace_root.join("src"))?;
+        let path = workspace_root.join("src/lib.rs");
+        fs::write(&path, "fn f() {\n    let  x =\t1;\n    let x = 1;\n}\n")?;
+
+        let mut source_lines = SourceLines::default();
+        let fingerprint = Fingerprint::new(
+            Some("unused_variables"),
+            &warning(&path, 2),
+            &workspace_root,
+            &mut source_lines,
+        );
+        assert_eq!(
+            fingerprint,
+            Fingerprint {
+                code: Some("unused_variables".to_owned()),
+                path: "src/lib.rs".to_owned(),
+                line: "let x = 1;".to_owned(),
+            }
+        );
+        let same_text_on_other_line = Fingerprint::new(
+            Some("unused_variables"),
+            &warning(&path, 3),
+            &workspace_root,
+            &mut source_lines,
+        );
+        assert_eq!(same_text_on_other_line, fingerprint);
+
+        let mut options = Options::default();
+        options.baseline_file = Some(PathBuf::from("baseline.json"));
+        let known = warning(&path, 2);
+        Baseline::new([(Some("unused_variables"), &known)], &workspace_root)
+            .save(&options, &workspace_root)?;
+        let baseline = Baseline::load(&options, &workspace_root)?;
+        assert_eq!(baseline.len(), 1);
+
+        // Edits above move the known warning, but it stays hidden
+        fs::write(&path, "\nfn f() {\n    let x = 1;\n    let y = 2;\n}\n")?;
+        let warnings = vec![
+            (Some("unused_variables"), warning(&path, 3)),
+            (Some("unused_variables"), warning(&path, 4)),
+            (Some("dead_code"), warning(&path, 3)),
+            (Some("unused_variables"), warning(&path, 3)),
+        ];
+        let remaining = baseline.filter(warnings, &workspace_root, |(code, location)| {
+            (*code, location)
+        });
+        assert_eq!(
+            remaining
+                .iter()
+                .map(|(code, location)| (*code, location.line))
+                .collect::<Vec<_>>(),
+            [
+                (Some("unused_variables"), 4),
+                (Some("dead_code"), 3),
+                (Some("unused_variables"), 3),
+            ]
+        );
+
+        fs::remove_dir_all(&workspace_root)?;
+        Ok(())
+    }
+}
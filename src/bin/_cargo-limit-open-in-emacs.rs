@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use cargo_limit::{
+    NO_EXIT_CODE,
+    models::{EditorData, Level, Location},
+};
+use std::{
+    io,
+    io::Read,
+    path::Path,
+    process::{Command, exit},
+};
+
+const EMACSCLIENT: &str = "emacsclient";
+
+/// Elisp which fills `compilation-mode` buffer of the workspace with the locations,
+/// so that `next-error` (`M-g n`) jumps to them.
+#[doc(hidden)]
+fn update_compilation_buffer_expr(editor_data: &EditorData) -> String {
+    let workspace_root = editor_data.workspace_root.to_string_lossy();
+    let buffer_name = format!("*cargo-limit: {workspace_root}*");
+    let default_directory = format!("{}/", workspace_root.trim_end_matches(['/', '\\']));
+    let text = editor_data
+        .locations
+        .iter()
+        .map(|location| compilation_line(location, &editor_data.workspace_root))
+        .collect::<String>();
+
+    format!(
+        r#"(let ((buffer (get-buffer-create {buffer_name})))
+  (with-current-buffer buffer
+    (let ((inhibit-read-only t))
+      (erase-buffer)
+      (setq default-directory {default_directory})
+      (insert {text})
+      (compilation-mode)
+      (goto-char (point-min))))
+  (setq next-error-last-buffer buffer)
+  (when (> (buffer-size buffer) 0)
+    (display-buffer buffer))
+  nil)"#,
+        buffer_name = elisp_string(&buffer_name),
+        default_directory = elisp_string(&default_directory),
+        text = elisp_string(&text),
+    )
+}
+
+// GNU format, which compilation-mode recognizes by default:
+// src/main.rs:6:5: error: message
+//   rest of the message
+#[doc(hidden)]
+fn compilation_line(location: &Location, workspace_root: &Path) -> String {
+    let path = location
+        .path
+        .strip_prefix(workspace_root)
+        .unwrap_or(&location.path);
+    let kind = match location.level {
+        Level::Warning => "warning",
+        Level::Note | Level::Help | Level::FailureNote => "note",
+        Level::Ice | Level::Error | Level::TestFailure => "error",
+    };
+    let mut lines = location.message.lines();
+    let mut result = format!(
+        "{}:{}:{}: {kind}: {}\n",
+        path.display(),
+        location.line,
+        location.column,
+        lines.next().unwrap_or_default()
+    );
+    for line in lines {
+        result.push_str("  ");
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
+
+#[doc(hidden)]
+fn elisp_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        if matches!(c, '"' | '\\') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result.push('"');
+    result
+}
+
+#[doc(hidden)]
+fn main() -> Result<()> {
+    let mut raw_editor_data = String::new();
+    io::stdin().read_to_string(&mut raw_editor_data)?;
+    let editor_data: EditorData = serde_json::from_str(&raw_editor_data)?;
+
+    let status = Command::new(EMACSCLIENT)
+        .args(["--eval", &update_compilation_buffer_expr(&editor_data)])
+        .status()
+        .with_context(|| format!("failed to execute {EMACSCLIENT:?}"))?;
+    exit(status.code().unwrap_or(NO_EXIT_CODE));
+}
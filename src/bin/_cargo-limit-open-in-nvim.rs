@@ -1,84 +1,39 @@
-use anyhow::{Error, Result};
-use cargo_limit::{NO_EXIT_CODE, NVIM, env_vars, models::EditorData};
+use anyhow::{Result, format_err};
+#[cfg(unix)]
+use cargo_limit::nvim_sockets::{is_owned_by_current_user, is_private_dir, sockets_dir};
+use cargo_limit::{
+    env_vars,
+    models::EditorData,
+    nvim_rpc::{NeovimClient, RpcError},
+};
+use rmpv::Value;
 use std::{
-    env, io,
+    env, fs, io,
     io::{Read, Write},
-    process::{Command, ExitStatus, Output, exit},
+    path::{Path, PathBuf},
 };
 
-#[doc(hidden)]
-struct NeovimCommand {
-    escaped_workspace_root: String,
-    command: String,
-}
-
-impl NeovimCommand {
-    fn new(command: &str, raw_editor_data: &str) -> Result<Self> {
-        let command = format!(r#"{command}({raw_editor_data})"#);
-
-        let editor_data: EditorData = serde_json::from_str(raw_editor_data)?;
-        let escaped_workspace_root = editor_data.escaped_workspace_root();
-
-        Ok(Self {
-            escaped_workspace_root,
-            command,
-        })
-    }
-
-    fn run(self) -> Result<ExitStatus> {
-        let server_name = nvim_listen_address(self.escaped_workspace_root)?;
-        let remote_send_args = vec![
-            "--headless",
-            "--clean",
-            "--server",
-            &server_name,
-            "--remote-expr",
-            &self.command,
-        ];
-
-        match Command::new(NVIM).args(remote_send_args).output() {
-            Ok(Output {
-                status,
-                stdout,
-                stderr,
-            }) => {
-                const EXPECTED_EXPR_RESULT: [u8; 1] = [b'0'];
-                if stdout != EXPECTED_EXPR_RESULT {
-                    let mut stdout_writer = io::stdout();
-                    stdout_writer.write_all(&stdout)?;
-                    stdout_writer.flush()?;
-                }
-
-                let failed_to_connect_is_the_only_error = stderr.starts_with(b"E247:")
-                    && stderr.iter().filter(|i| **i == b'\n').count() == 1;
-                if !failed_to_connect_is_the_only_error {
-                    let mut stderr_writer = io::stderr();
-                    stderr_writer.write_all(&stderr)?;
-                    stderr_writer.flush()?;
-                }
-
-                Ok(status)
-            },
-            Err(err) => Err(Error::from(err)),
-        }
-    }
-}
+const OPEN_FUNCTION: &str = "g:CargoLimitOpen";
 
+/// Sockets (or named pipes on Windows) of all Neovim instances open on the workspace:
+/// each instance listens on `<escaped workspace root>-<pid>`.
 #[doc(hidden)]
-fn nvim_listen_address(escaped_workspace_root: String) -> Result<String> {
-    const PREFIX: &str = "nvim-cargo-limit-";
-
-    let result = {
-        let user = env::var(env_vars::USER)?;
-
+fn nvim_listen_addresses(escaped_workspace_root: String) -> Result<Vec<PathBuf>> {
+    let (dir, instance_prefix) = {
         #[cfg(unix)]
         {
-            format!("/tmp/{PREFIX}{user}/{escaped_workspace_root}")
+            (sockets_dir(), format!("{escaped_workspace_root}-"))
         }
 
         #[cfg(windows)]
         {
-            format!(r"\\.\pipe\{PREFIX}{user}-{escaped_workspace_root}")
+            const PREFIX: &str = "nvim-cargo-limit-";
+            // The plugin uses empty $USERNAME too
+            let user = env::var(env_vars::USER).unwrap_or_default();
+            (
+                PathBuf::from(r"\\.\pipe\"),
+                format!("{PREFIX}{user}-{escaped_workspace_root}-"),
+            )
         }
 
         #[cfg(not(any(unix, windows)))]
@@ -87,14 +42,97 @@ fn nvim_listen_address(escaped_workspace_root: String) -> Result<String> {
         }
     };
 
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    #[cfg(unix)]
+    if !is_private_dir(&fs::symlink_metadata(&dir)?) {
+        return Err(format_err!(
+            "{} is not trusted: it must be owned by current user and have 0700 permissions",
+            dir.display()
+        ));
+    }
+
+    let mut result = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        #[cfg(unix)]
+        if !is_owned_by_current_user(&entry.metadata()?) {
+            continue;
+        }
+        let file_name = entry.file_name();
+        let is_instance = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix(&instance_prefix))
+            .is_some_and(|pid| !pid.is_empty() && pid.bytes().all(|i| i.is_ascii_digit()));
+        if is_instance {
+            result.push(dir.join(file_name));
+        }
+    }
+    result.sort();
     Ok(result)
 }
 
+/// Returns `false` if nothing listens on the address.
+#[doc(hidden)]
+fn open_in_instance(address: &Path, editor_data: &EditorData) -> Result<bool, RpcError> {
+    let result = match NeovimClient::connect(address) {
+        Ok(mut client) => client.call_function(OPEN_FUNCTION, &[editor_data])?,
+        Err(err) if err.is_not_listening() => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    let is_expected_result = matches!(result, Value::Nil) || result.as_i64() == Some(0);
+    if !is_expected_result {
+        let mut stdout_writer = io::stdout();
+        writeln!(stdout_writer, "{result}")?;
+        stdout_writer.flush()?;
+    }
+    Ok(true)
+}
+
 #[doc(hidden)]
 fn main() -> Result<()> {
     let mut raw_editor_data = String::new();
     io::stdin().read_to_string(&mut raw_editor_data)?;
+    let editor_data: EditorData = serde_json::from_str(&raw_editor_data)?;
+
+    // Set by nvim for :terminal and jobs, so its own instance is updated
+    // even if it's not open on the workspace
+    if let Some(parent_address) = env::var_os(env_vars::NVIM) {
+        let parent_address = Path::new(&parent_address);
+        match open_in_instance(parent_address, &editor_data) {
+            Ok(true) => return Ok(()),
+            Ok(false) => (),
+            Err(err) => eprintln!("{}: {err}", parent_address.display()),
+        }
+    }
 
-    let command = NeovimCommand::new("g:CargoLimitOpen", &raw_editor_data)?;
-    exit(command.run()?.code().unwrap_or(NO_EXIT_CODE));
+    let addresses = nvim_listen_addresses(editor_data.escaped_workspace_root())?;
+    let mut failed = 0;
+    for address in &addresses {
+        match open_in_instance(address, &editor_data) {
+            Ok(true) => (),
+            Ok(false) => {
+                // Socket of nvim which was killed before it could remove it
+                #[cfg(unix)]
+                let _ = fs::remove_file(address);
+            },
+            Err(err) => {
+                eprintln!("{}: {err}", address.display());
+                failed += 1;
+            },
+        }
+    }
+
+    if failed > 0 {
+        Err(format_err!(
+            "failed to update {failed} of {} nvim instances",
+            addresses.len()
+        ))
+    } else {
+        Ok(())
+    }
 }
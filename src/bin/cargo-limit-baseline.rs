@@ -0,0 +1,17 @@
+use anyhow::Result;
+use std::{env, process::exit};
+
+const SUBCOMMAND_NAME: &str = "limit-baseline";
+const DEFAULT_CARGO_SUBCOMMAND: &str = "check";
+
+#[doc(hidden)]
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some(SUBCOMMAND_NAME) {
+        let _ = args.next();
+    }
+    let cargo_subcommand = args
+        .next_if(|arg| !arg.starts_with('-'))
+        .unwrap_or_else(|| DEFAULT_CARGO_SUBCOMMAND.to_owned());
+    exit(cargo_limit::update_baseline(&cargo_subcommand, args)?);
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use cargo_limit::{
+    collect_editor_data,
+    models::{EditorData, Level, Location},
+    uri::{path_to_uri, uri_to_path},
+};
+use serde_json::{Value, json};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    io::{self, BufRead, Write},
+    iter,
+    path::PathBuf,
+    process::exit,
+};
+
+const COLOR_NEVER: &str = "--color=never";
+const CONTENT_LENGTH: &str = "Content-Length";
+const METHOD_NOT_FOUND: i32 = -32601;
+const ERROR_MESSAGE_TYPE: i32 = 1;
+const SOURCE: &str = "cargo-limit";
+
+struct Server {
+    cargo_args: Vec<String>,
+    published_uris: BTreeSet<String>,
+    shutdown_requested: bool,
+    stdout: io::Stdout,
+}
+
+impl Server {
+    fn new(cargo_args: Vec<String>) -> Self {
+        Self {
+            cargo_args,
+            published_uris: BTreeSet::new(),
+            shutdown_requested: false,
+            stdout: io::stdout(),
+        }
+    }
+
+    fn run(mut self) -> Result<i32> {
+        let mut stdin = io::stdin().lock();
+        while let Some(message) = read_message(&mut stdin)? {
+            let id = message.get("id").cloned();
+            let method = message.get("method").and_then(Value::as_str);
+            match (method, id) {
+                (Some("initialize"), Some(id)) => {
+                    if let Some(root) = root_path(&message["params"]) {
+                        env::set_current_dir(root)?;
+                    }
+                    self.respond(
+                        id,
+                        json!({
+                            "capabilities": {
+                                "textDocumentSync": {
+                                    "openClose": true,
+                                    "change": 0,
+                                    "save": { "includeText": false },
+                                },
+                            },
+                            "serverInfo": {
+                                "name": SOURCE,
+                                "version": env!("CARGO_PKG_VERSION"),
+                            },
+                        }),
+                    )?;
+                },
+                (Some("initialized" | "textDocument/didSave"), None) => {
+                    // Next save may fix the error, like invalid .cargo-limit.toml
+                    if let Err(err) = self.check() {
+                        self.log_message(ERROR_MESSAGE_TYPE, &format!("{err:#}"))?;
+                    }
+                },
+                (Some("shutdown"), Some(id)) => {
+                    self.shutdown_requested = true;
+                    self.respond(id, Value::Null)?;
+                },
+                (Some("exit"), None) => {
+                    return Ok(if self.shutdown_requested { 0 } else { 1 });
+                },
+                (Some(method), Some(id)) => {
+                    self.respond_error(
+                        id,
+                        METHOD_NOT_FOUND,
+                        &format!("unsupported method {method}"),
+                    )?;
+                },
+                _ => (),
+            }
+        }
+        Ok(1)
+    }
+
+    fn check(&mut self) -> Result<()> {
+        let editor_data = collect_editor_data(self.cargo_args.clone().into_iter())?;
+        let diagnostics = editor_data
+            .map(|EditorData { locations, .. }| group_diagnostics_by_uris(locations))
+            .unwrap_or_default();
+
+        let outdated_uris = self
+            .published_uris
+            .iter()
+            .filter(|uri| !diagnostics.contains_key(*uri))
+            .cloned()
+            .collect::<Vec<_>>();
+        for uri in outdated_uris {
+            self.publish_diagnostics(&uri, Vec::new())?;
+        }
+
+        self.published_uris = diagnostics.keys().cloned().collect();
+        for (uri, diagnostics) in diagnostics {
+            self.publish_diagnostics(&uri, diagnostics)?;
+        }
+        Ok(())
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: Vec<Value>) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn log_message(&mut self, message_type: i32, message: &str) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "window/logMessage",
+            "params": { "type": message_type, "message": message },
+        }))
+    }
+
+    fn respond(&mut self, id: Value, result: Value) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    fn respond_error(&mut self, id: Value, code: i32, message: &str) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }))
+    }
+
+    fn send(&mut self, message: Value) -> Result<()> {
+        let body = serde_json::to_string(&message)?;
+        write!(
+            self.stdout,
+            "{CONTENT_LENGTH}: {}\r\n\r\n{body}",
+            body.len()
+        )?;
+        self.stdout.flush()?;
+        Ok(())
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        } else if let Some((name, length)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case(CONTENT_LENGTH)
+        {
+            content_length = Some(length.trim().parse::<usize>()?);
+        }
+    }
+
+    let content_length = content_length.context("missing Content-Length header")?;
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+fn group_diagnostics_by_uris(locations: Vec<Location>) -> BTreeMap<String, Vec<Value>> {
+    let mut result = BTreeMap::<String, Vec<Value>>::new();
+    for location in locations {
+        let position = lsp_position(location.line, location.column);
+        let severity = match location.level {
+            Level::Ice | Level::Error | Level::TestFailure => 1,
+            Level::Warning => 2,
+            Level::Note => 3,
+            _ => 4,
+        };
+        let related_information = location
+            .related
+            .iter()
+            .map(|related| {
+                let position = lsp_position(related.line, related.column);
+                json!({
+                    "location": {
+                        "uri": path_to_uri(&related.path),
+                        "range": { "start": position, "end": position },
+                    },
+                    "message": related.label.as_deref().unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>();
+        result
+            .entry(path_to_uri(&location.path))
+            .or_default()
+            .push(json!({
+                "range": { "start": position, "end": position },
+                "severity": severity,
+                "source": SOURCE,
+                "message": location.message,
+                "relatedInformation": related_information,
+            }));
+    }
+    result
+}
+
+fn lsp_position(line: usize, column: usize) -> Value {
+    json!({
+        "line": line.saturating_sub(1),
+        "character": column.saturating_sub(1),
+    })
+}
+
+fn root_path(params: &Value) -> Option<PathBuf> {
+    params
+        .get("rootUri")
+        .and_then(Value::as_str)
+        .and_then(uri_to_path)
+        .or_else(|| {
+            params
+                .get("rootPath")
+                .and_then(Value::as_str)
+                .map(PathBuf::from)
+        })
+}
+
+fn main() -> Result<()> {
+    let cargo_args = iter::once(COLOR_NEVER.to_owned())
+        .chain(env::args().skip(1))
+        .collect();
+    exit(Server::new(cargo_args).run()?);
+}
@@ -0,0 +1,308 @@
+use crate::{
+    models::{Level, Location},
+    panic::{Panic, PanicParser, parse_location},
+};
+use cargo_metadata::{Metadata, TargetKind};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const WARNING_PREFIX: &str = "warning: ";
+const WARNING_DIRECTIVES: &[&str] = &["cargo::warning=", "cargo:warning="];
+const FAILURE_PREFIX: &str = "error: failed to run custom build command for `";
+const FAILURE_DETAILS_INDENT: &str = "  ";
+const FAILURE_CONTINUATIONS: &[&str] = &["note: ", "Caused by:"];
+
+// gcc/clang and MSVC ones respectively
+const COMPILER_DIAGNOSTIC_MARKERS: &[(&str, Level)] = &[
+    (": fatal error: ", Level::Error),
+    (": error: ", Level::Error),
+    (": warning: ", Level::Warning),
+    (": fatal error ", Level::Error),
+    (": error ", Level::Error),
+    (": warning ", Level::Warning),
+];
+
+pub struct BuildScriptWarning {
+    package: String,
+    version: String,
+    message: String,
+}
+
+#[derive(Default)]
+pub struct BuildScriptFailures {
+    current: Option<BuildScriptFailure>,
+    failures: Vec<BuildScriptFailure>,
+}
+
+pub struct BuildScriptFailure {
+    package_dir: PathBuf,
+    panic_parser: PanicParser,
+    diagnostics: Vec<CompilerDiagnostic>,
+}
+
+struct CompilerDiagnostic {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    level: Level,
+    message: String,
+}
+
+impl BuildScriptWarning {
+    // "warning: package@0.1.0: message"
+    pub fn parse(line: &str) -> Option<Self> {
+        let (package, message) = line.strip_prefix(WARNING_PREFIX)?.split_once(": ")?;
+        let (package, version) = package.split_once('@')?;
+        let is_valid = |name: &str| !name.is_empty() && !name.contains(char::is_whitespace);
+        (is_valid(package) && is_valid(version)).then(|| Self {
+            package: package.to_owned(),
+            version: version.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+
+    pub fn into_location(self, metadata: &Metadata) -> Option<Location> {
+        let package = metadata.workspace_packages().into_iter().find(|package| {
+            package.name == self.package && package.version.to_string() == self.version
+        })?;
+
+        // cc crate forwards C compiler messages as warnings
+        let package_dir = package.manifest_path.parent()?.as_std_path();
+        if let Some(diagnostic) = CompilerDiagnostic::parse(&self.message)
+            && let Some(location) =
+                diagnostic.into_location(package_dir, metadata.workspace_root.as_std_path())
+        {
+            return Some(location);
+        }
+
+        let path = package
+            .targets
+            .iter()
+            .find(|target| target.kind.contains(&TargetKind::CustomBuild))?
+            .src_path
+            .clone()
+            .into_std_path_buf();
+
+        // The message is usually printed with format arguments, so its exact line is a guess
+        let source = fs::read_to_string(&path).unwrap_or_default();
+        let find_directive = |line: &str| {
+            WARNING_DIRECTIVES
+                .iter()
+                .find_map(|directive| Some((line.find(directive)?, directive.len())))
+        };
+        let (line, column) = find_in_lines(&source, |line| {
+            let (start, len) = find_directive(line)?;
+            let literal = &line[start + len..];
+            let literal = literal.split(['{', '"']).next().unwrap_or_default();
+            (!literal.is_empty() && self.message.starts_with(literal)).then_some(start)
+        })
+        .or_else(|| find_in_lines(&source, |line| find_directive(line).map(|(start, _)| start)))
+        .unwrap_or((1, 1));
+
+        Some(Location {
+            path,
+            line,
+            column,
+            message: self.message,
+            level: Level::Warning,
+            label: None,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        })
+    }
+}
+
+impl BuildScriptFailures {
+    // error: failed to run custom build command for `package v0.1.0 (/path/to/package)`
+    //
+    // Caused by:
+    //   process didn't exit successfully: `...` (exit status: 101)
+    //   --- stdout
+    //   cargo:warning=src/foo.c:1:2: error: ...
+    //   --- stderr
+    //   thread 'main' panicked at build.rs:3:5:
+    pub fn push_line(&mut self, line: &str) {
+        if let Some(package) = line.strip_prefix(FAILURE_PREFIX) {
+            self.flush();
+            self.current = package
+                .strip_suffix(")`")
+                .and_then(|package| package.rsplit_once(" ("))
+                .map(|(_, package_dir)| BuildScriptFailure {
+                    package_dir: PathBuf::from(package_dir),
+                    panic_parser: PanicParser::default(),
+                    diagnostics: Vec::new(),
+                });
+            return;
+        }
+
+        let Some(failure) = &mut self.current else {
+            return;
+        };
+        if let Some(details) = line.strip_prefix(FAILURE_DETAILS_INDENT) {
+            failure.panic_parser.push_line(details);
+            let details = WARNING_DIRECTIVES
+                .iter()
+                .find_map(|directive| details.strip_prefix(directive))
+                .unwrap_or(details);
+            failure
+                .diagnostics
+                .extend(CompilerDiagnostic::parse(details));
+        } else if !line.is_empty() && !FAILURE_CONTINUATIONS.iter().any(|i| line.starts_with(i)) {
+            self.flush();
+        }
+    }
+
+    pub fn finish(mut self) -> Vec<BuildScriptFailure> {
+        self.flush();
+        self.failures
+    }
+
+    fn flush(&mut self) {
+        self.failures.extend(self.current.take());
+    }
+}
+
+impl BuildScriptFailure {
+    pub fn into_locations(self, workspace_root: &Path) -> Vec<Location> {
+        let diagnostics = self
+            .diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.level == Level::Error)
+            .filter_map(|diagnostic| diagnostic.into_location(&self.package_dir, workspace_root));
+        // Build script runs in its package directory
+        let panics = self
+            .panic_parser
+            .finish()
+            .into_iter()
+            .flat_map(|panic: Panic| panic.into_locations(workspace_root, &self.package_dir));
+        diagnostics.chain(panics).collect()
+    }
+}
+
+impl CompilerDiagnostic {
+    // "src/foo.c:1:2: error: message" or "src\foo.c(1,2): error C1234: message"
+    fn parse(line: &str) -> Option<Self> {
+        let (location, level, message) =
+            COMPILER_DIAGNOSTIC_MARKERS
+                .iter()
+                .find_map(|(marker, level)| {
+                    let (location, message) = line.split_once(marker)?;
+                    Some((location, *level, message))
+                })?;
+        let (path, line, column) = match location.strip_suffix(')') {
+            Some(location) => {
+                let (path, line_and_column) = location.rsplit_once('(')?;
+                let (line, column) = line_and_column
+                    .split_once(',')
+                    .unwrap_or((line_and_column, "1"));
+                (
+                    PathBuf::from(path),
+                    line.parse().ok()?,
+                    column.parse().ok()?,
+                )
+            },
+            None => parse_location(location)?,
+        };
+        Some(Self {
+            path,
+            line,
+            column,
+            level,
+            message: message.to_owned(),
+        })
+    }
+
+    fn into_location(self, package_dir: &Path, workspace_root: &Path) -> Option<Location> {
+        let path = package_dir
+            .join(&self.path)
+            .components()
+            .collect::<PathBuf>();
+        (path.starts_with(workspace_root) && path.is_file()).then_some(Location {
+            path,
+            line: self.line,
+            column: self.column,
+            message: self.message,
+            level: self.level,
+            label: None,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        })
+    }
+}
+
+fn find_in_lines(
+    source: &str,
+    mut find: impl FnMut(&str) -> Option<usize>,
+) -> Option<(usize, usize)> {
+    source
+        .lines()
+        .enumerate()
+        .find_map(|(index, line)| Some((index + 1, find(line)? + 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn failure_with_compiler_errors_and_panic() {
+        let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let stderr = format!(
+            "   Compiling app v0.1.0 ({root})
+error: failed to run custom build command for `app v0.1.0 ({root})`
+note: To improve backtraces for build dependencies, set the \
+             CARGO_PROFILE_DEV_BUILD_OVERRIDE_DEBUG=true environment variable to enable debug \
+             information generation.
+
+Caused by:
+  process didn't exit successfully: `{root}/target/debug/build/app-0123/build-script-build` (exit \
+             status: 101)
+  --- stdout
+  cargo:warning=src/models.rs:3:7: warning: unused variable 'x'
+  cargo:warning=src/io.rs:2:11: error: expected ';' before '}}' token
+
+  --- stderr
+  src\\lib.rs(4,1): error C2143: syntax error: missing ';' before '}}'
+  thread 'main' (385) panicked at src/panic.rs:8:9:
+  cc failed
+  note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+warning: build failed, waiting for other jobs to finish...
+",
+            root = workspace_root.display()
+        );
+
+        let mut failures = BuildScriptFailures::default();
+        for line in stderr.lines() {
+            failures.push_line(line);
+        }
+        let locations = failures
+            .finish()
+            .into_iter()
+            .flat_map(|failure| failure.into_locations(workspace_root))
+            .map(|location| {
+                (
+                    location
+                        .path
+                        .strip_prefix(workspace_root)
+                        .unwrap()
+                        .to_owned(),
+                    location.line,
+                    location.column,
+                    location.level,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut expected = vec![
+            (PathBuf::from("src/io.rs"), 2, 11, Level::Error),
+            ("src/panic.rs".into(), 8, 9, Level::Error),
+        ];
+        if cfg!(windows) {
+            expected.insert(1, ("src/lib.rs".into(), 4, 1, Level::Error));
+        }
+        assert_eq!(locations, expected);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{env_vars, stderr::Progress};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+pub const CONFIG_FILE_NAME: &str = ".cargo-limit.toml";
+
+const USER_CONFIG_DIR_NAME: &str = "cargo-limit";
+const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub msg_limit: Option<usize>,
+    pub time_limit: Option<u64>,
+    pub asc: Option<bool>,
+    pub force_warn: Option<bool>,
+    pub deps_warn: Option<bool>,
+    pub editor: Option<String>,
+    pub watch: Option<bool>,
+    pub checkstyle_report: Option<PathBuf>,
+    pub junit_report: Option<PathBuf>,
+    pub gitlab_report: Option<PathBuf>,
+    pub warn_budget: Option<usize>,
+    pub warn_budget_file: Option<PathBuf>,
+    pub warn_ratchet: Option<bool>,
+    pub baseline: Option<PathBuf>,
+    pub test_summary: Option<bool>,
+    pub progress: Option<Progress>,
+    pub interactive: Option<bool>,
+    pub include_paths: Option<Vec<String>>,
+    pub exclude_paths: Option<Vec<String>>,
+    pub include_lints: Option<Vec<String>>,
+    pub exclude_lints: Option<Vec<String>>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let user_config = match user_config_path() {
+            Some(path) => Self::parse_if_exists(&path)?,
+            None => Self::default(),
+        };
+        let workspace_config = match find_workspace_config(&env::current_dir()?) {
+            Some(path) => Self::parse_if_exists(&path)?,
+            None => Self::default(),
+        };
+        Ok(workspace_config.or(user_config))
+    }
+
+    fn parse_if_exists(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    fn or(self, base: Self) -> Self {
+        Self {
+            msg_limit: self.msg_limit.or(base.msg_limit),
+            time_limit: self.time_limit.or(base.time_limit),
+            asc: self.asc.or(base.asc),
+            force_warn: self.force_warn.or(base.force_warn),
+            deps_warn: self.deps_warn.or(base.deps_warn),
+            editor: self.editor.or(base.editor),
+            watch: self.watch.or(base.watch),
+            checkstyle_report: self.checkstyle_report.or(base.checkstyle_report),
+            junit_report: self.junit_report.or(base.junit_report),
+            gitlab_report: self.gitlab_report.or(base.gitlab_report),
+            warn_budget: self.warn_budget.or(base.warn_budget),
+            warn_budget_file: self.warn_budget_file.or(base.warn_budget_file),
+            warn_ratchet: self.warn_ratchet.or(base.warn_ratchet),
+            baseline: self.baseline.or(base.baseline),
+            test_summary: self.test_summary.or(base.test_summary),
+            progress: self.progress.or(base.progress),
+            interactive: self.interactive.or(base.interactive),
+            include_paths: self.include_paths.or(base.include_paths),
+            exclude_paths: self.exclude_paths.or(base.exclude_paths),
+            include_lints: self.include_lints.or(base.include_lints),
+            exclude_lints: self.exclude_lints.or(base.exclude_lints),
+        }
+    }
+}
+
+fn find_workspace_config(current_dir: &Path) -> Option<PathBuf> {
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let config_dir = {
+        #[cfg(unix)]
+        {
+            env::var_os(env_vars::XDG_CONFIG_HOME)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+                .or_else(|| {
+                    env::var_os(env_vars::HOME).map(|home| Path::new(&home).join(".config"))
+                })
+        }
+
+        #[cfg(windows)]
+        {
+            env::var_os(env_vars::APPDATA).map(PathBuf::from)
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        {
+            compile_error!("this platform is unsupported")
+        }
+    };
+    config_dir.map(|dir| dir.join(USER_CONFIG_DIR_NAME).join(USER_CONFIG_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse() -> Result<()> {
+        assert_eq!(Config::parse("")?, Config::default());
+        assert_eq!(
+            Config::parse(
+                r#"
+                msg-limit = 3
+                time-limit = 0
+                force-warn = true
+                editor = ""
+                exclude-paths = ["generated/**"]
+                "#
+            )?,
+            Config {
+                msg_limit: Some(3),
+                time_limit: Some(0),
+                force_warn: Some(true),
+                editor: Some("".to_owned()),
+                exclude_paths: Some(vec!["generated/**".to_owned()]),
+                ..Config::default()
+            }
+        );
+        assert!(Config::parse("unknown-key = 1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn workspace_config_overrides_user_config() {
+        let user_config = Config {
+            msg_limit: Some(1),
+            asc: Some(true),
+            ..Config::default()
+        };
+        let workspace_config = Config {
+            msg_limit: Some(2),
+            deps_warn: Some(false),
+            ..Config::default()
+        };
+        assert_eq!(
+            workspace_config.or(user_config),
+            Config {
+                msg_limit: Some(2),
+                asc: Some(true),
+                deps_warn: Some(false),
+                ..Config::default()
+            }
+        );
+    }
+}
@@ -2,14 +2,38 @@ use const_format::concatcp;
 
 pub const CARGO: &str = "CARGO";
 pub const RUSTFLAGS: &str = "RUSTFLAGS";
+pub const GITHUB_ACTIONS: &str = "GITHUB_ACTIONS";
+pub const GITLAB_CI: &str = "GITLAB_CI";
+pub const NVIM: &str = "NVIM";
 pub const TERM_COLOR: &str = concatcp!(CARGO, "_TERM_COLOR");
+pub const TERM_PROGRESS_WHEN: &str = concatcp!(CARGO, "_TERM_PROGRESS_WHEN");
 
 pub const ASC: &str = concatcp!(CARGO, "_ASC");
+pub const BASELINE: &str = concatcp!(CARGO, "_BASELINE");
+pub const CHECKSTYLE_REPORT: &str = concatcp!(CARGO, "_CHECKSTYLE_REPORT");
 pub const DEPS_WARN: &str = concatcp!(CARGO, "_DEPS_WARN");
 pub const EDITOR: &str = concatcp!(CARGO, "_EDITOR");
 pub const FORCE_WARN: &str = concatcp!(CARGO, "_FORCE_WARN");
+pub const GITLAB_REPORT: &str = concatcp!(CARGO, "_GITLAB_REPORT");
+pub const INTERACTIVE: &str = concatcp!(CARGO, "_INTERACTIVE");
+pub const JUNIT_REPORT: &str = concatcp!(CARGO, "_JUNIT_REPORT");
 pub const MSG_LIMIT: &str = concatcp!(CARGO, "_MSG_LIMIT");
+pub const PROGRESS: &str = concatcp!(CARGO, "_PROGRESS");
+pub const TEST_SUMMARY: &str = concatcp!(CARGO, "_TEST_SUMMARY");
 pub const TIME_LIMIT: &str = concatcp!(CARGO, "_TIME_LIMIT");
+pub const WARN_BUDGET: &str = concatcp!(CARGO, "_WARN_BUDGET");
+pub const WARN_BUDGET_FILE: &str = concatcp!(CARGO, "_WARN_BUDGET_FILE");
+pub const WARN_RATCHET: &str = concatcp!(CARGO, "_WARN_RATCHET");
+pub const WATCH: &str = concatcp!(CARGO, "_WATCH");
+
+#[cfg(windows)]
+pub const APPDATA: &str = "APPDATA";
+#[cfg(unix)]
+pub const HOME: &str = "HOME";
+#[cfg(unix)]
+pub const XDG_CONFIG_HOME: &str = "XDG_CONFIG_HOME";
+#[cfg(unix)]
+pub const XDG_RUNTIME_DIR: &str = "XDG_RUNTIME_DIR";
 
 pub const USER: &str = {
     #[cfg(unix)]
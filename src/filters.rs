@@ -0,0 +1,286 @@
+use crate::{models::Location, options::Options};
+use anyhow::{Context, Result};
+use cargo_metadata::CompilerMessage;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+const GROUP_MARKERS: &[&str] = &["implied by `", "part of `"];
+
+pub struct Filters {
+    include_paths: Option<GlobSet>,
+    exclude_paths: GlobSet,
+    include_lints: Option<GlobSet>,
+    exclude_lints: GlobSet,
+}
+
+#[derive(Default)]
+pub struct LintGroups {
+    groups: HashMap<String, HashSet<String>>,
+}
+
+impl Filters {
+    pub fn new(options: &Options) -> Result<Self> {
+        let non_empty = |globs: GlobSet| if globs.is_empty() { None } else { Some(globs) };
+        Ok(Self {
+            include_paths: non_empty(build_path_globs(&options.include_paths)?),
+            exclude_paths: build_path_globs(&options.exclude_paths)?,
+            include_lints: non_empty(build_lint_globs(&options.include_lints)?),
+            exclude_lints: build_lint_globs(&options.exclude_lints)?,
+        })
+    }
+
+    /// Lint `code` is absent for messages which aren't lints, like build script warnings.
+    pub fn is_visible(
+        &self,
+        code: Option<&str>,
+        location: &Location,
+        lint_groups: &LintGroups,
+        workspace_root: &Path,
+    ) -> bool {
+        let path = location
+            .path
+            .strip_prefix(workspace_root)
+            .unwrap_or(&location.path);
+        let path_is_included = self
+            .include_paths
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path));
+        if !path_is_included || self.exclude_paths.is_match(path) {
+            return false;
+        }
+
+        let lints = code
+            .map(|code| lint_groups.lint_with_groups(code))
+            .unwrap_or_default();
+        let lint_is_included = self
+            .include_lints
+            .as_ref()
+            .is_none_or(|globs| lints.iter().any(|lint| globs.is_match(lint)));
+        lint_is_included && !lints.iter().any(|lint| self.exclude_lints.is_match(lint))
+    }
+}
+
+impl LintGroups {
+    // Group membership is only mentioned in notes like
+    // "`-W clippy::doc-markdown` implied by `-W clippy::pedantic`"
+    // or "`#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default",
+    // which are attached to the first occurrence of each lint
+    pub fn parse<'m>(messages: impl IntoIterator<Item = &'m CompilerMessage>) -> Self {
+        let mut result = Self::default();
+        for message in messages {
+            let Some(code) = &message.message.code else {
+                continue;
+            };
+            let groups = message
+                .message
+                .children
+                .iter()
+                .flat_map(|child| {
+                    GROUP_MARKERS
+                        .iter()
+                        .filter_map(|marker| child.message.split_once(marker))
+                })
+                .filter_map(|(_, group)| group.split_once('`'))
+                .map(|(group, _)| normalize_lint(group));
+            result
+                .groups
+                .entry(code.code.clone())
+                .or_default()
+                .extend(groups);
+        }
+        result
+    }
+
+    fn lint_with_groups(&self, lint: &str) -> Vec<String> {
+        let mut result = vec![lint.to_owned()];
+        if let Some(groups) = self.groups.get(lint) {
+            result.extend(groups.iter().cloned());
+        }
+        result
+    }
+}
+
+fn normalize_lint(flag: &str) -> String {
+    let lint = match flag.strip_prefix("#[") {
+        Some(attribute) => attribute
+            .split_once('(')
+            .and_then(|(_, lint)| lint.split_once(')'))
+            .map(|(lint, _)| lint)
+            .unwrap_or(attribute),
+        None => flag.split_whitespace().last().unwrap_or(flag),
+    };
+    lint.replace('-', "_")
+}
+
+fn build_path_globs(globs: &[String]) -> Result<GlobSet> {
+    build_globs(globs, |glob| {
+        GlobBuilder::new(glob).literal_separator(true).build()
+    })
+}
+
+fn build_lint_globs(globs: &[String]) -> Result<GlobSet> {
+    build_globs(globs, |glob| Glob::new(&normalize_lint(glob)))
+}
+
+fn build_globs(
+    globs: &[String],
+    parse: impl Fn(&str) -> Result<Glob, globset::Error>,
+) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(parse(glob).with_context(|| format!("invalid pattern '{glob}'"))?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Level;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    const WORKSPACE_ROOT: &str = "/workspace";
+
+    // Lines of `cargo clippy --message-format json` without spans and rendered text
+    const MESSAGES: &str = r#"
+{"reason":"compiler-message","package_id":"path+file:///workspace#app@0.1.0","manifest_path":"/workspace/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/workspace/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"help","message":"for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#doc_markdown","rendered":null,"spans":[]},{"children":[],"code":null,"level":"note","message":"`-W clippy::doc-markdown` implied by `-W clippy::pedantic`","rendered":null,"spans":[]},{"children":[],"code":null,"level":"help","message":"to override `-W clippy::pedantic` add `#[allow(clippy::doc_markdown)]`","rendered":null,"spans":[]}],"code":{"code":"clippy::doc_markdown","explanation":null},"level":"warning","message":"item in documentation is missing backticks","rendered":null,"spans":[]}}
+{"reason":"compiler-message","package_id":"path+file:///workspace#app@0.1.0","manifest_path":"/workspace/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/workspace/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"note","message":"`#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default","rendered":null,"spans":[]}],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","rendered":null,"spans":[]}}
+{"reason":"compiler-message","package_id":"path+file:///workspace#app@0.1.0","manifest_path":"/workspace/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/workspace/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"$message_type":"diagnostic","children":[],"code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `y`","rendered":null,"spans":[]}}
+"#;
+
+    fn lint_groups() -> LintGroups {
+        let messages = MESSAGES
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_str::<CompilerMessage>(line).unwrap())
+            .collect::<Vec<_>>();
+        LintGroups::parse(&messages)
+    }
+
+    fn filters(
+        include_paths: &[&str],
+        exclude_paths: &[&str],
+        include_lints: &[&str],
+        exclude_lints: &[&str],
+    ) -> Filters {
+        let to_strings = |globs: &[&str]| globs.iter().map(|i| i.to_string()).collect();
+        let mut options = Options::default();
+        options.include_paths = to_strings(include_paths);
+        options.exclude_paths = to_strings(exclude_paths);
+        options.include_lints = to_strings(include_lints);
+        options.exclude_lints = to_strings(exclude_lints);
+        Filters::new(&options).unwrap()
+    }
+
+    // Which of the warnings are visible
+    fn visible(filters: &Filters) -> Vec<(Option<&'static str>, &'static str)> {
+        let lint_groups = lint_groups();
+        [
+            (Some("clippy::doc_markdown"), "src/lib.rs"),
+            (Some("unused_variables"), "src/generated/mod.rs"),
+            (Some("dead_code"), "tests/a.rs"),
+            (None, "build.rs"),
+        ]
+        .into_iter()
+        .filter(|(code, path)| {
+            let location = Location {
+                path: PathBuf::from(WORKSPACE_ROOT).join(path),
+                line: 1,
+                column: 1,
+                message: String::new(),
+                level: Level::Warning,
+                label: None,
+                related: Vec::new(),
+                fixes: Vec::new(),
+            };
+            filters.is_visible(*code, &location, &lint_groups, Path::new(WORKSPACE_ROOT))
+        })
+        .collect()
+    }
+
+    #[test]
+    fn parse_lint_groups() {
+        let lint_groups = lint_groups();
+        let sorted = |lint| {
+            let mut lints = lint_groups.lint_with_groups(lint);
+            lints.sort();
+            lints
+        };
+        assert_eq!(
+            sorted("clippy::doc_markdown"),
+            ["clippy::doc_markdown", "clippy::pedantic"]
+        );
+        assert_eq!(sorted("unused_variables"), ["unused", "unused_variables"]);
+        assert_eq!(sorted("dead_code"), ["dead_code"]);
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(visible(&filters(&[], &[], &[], &[])).len(), 4);
+        assert_eq!(
+            visible(&filters(&["src/**"], &["src/generated/**"], &[], &[])),
+            [(Some("clippy::doc_markdown"), "src/lib.rs")]
+        );
+        // Separators aren't matched by "*"
+        assert_eq!(
+            visible(&filters(&["src/*"], &[], &[], &[])),
+            [(Some("clippy::doc_markdown"), "src/lib.rs")]
+        );
+        assert_eq!(
+            visible(&filters(&[], &["build.rs", "tests/**"], &[], &[])),
+            [
+                (Some("clippy::doc_markdown"), "src/lib.rs"),
+                (Some("unused_variables"), "src/generated/mod.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lints() {
+        assert_eq!(
+            visible(&filters(&[], &[], &["clippy::*"], &[])),
+            [(Some("clippy::doc_markdown"), "src/lib.rs")]
+        );
+        // Groups and names with dashes, like in -W flags
+        assert_eq!(
+            visible(&filters(&[], &[], &[], &["clippy::pedantic", "dead-code"])),
+            [
+                (Some("unused_variables"), "src/generated/mod.rs"),
+                (None, "build.rs"),
+            ]
+        );
+        assert_eq!(
+            visible(&filters(&[], &[], &["unused"], &[])),
+            [(Some("unused_variables"), "src/generated/mod.rs")]
+        );
+    }
+
+    #[test]
+    fn paths_and_lints() {
+        // Both have to be included and neither excluded
+        assert_eq!(
+            visible(&filters(&["src/**"], &[], &["unused"], &[])),
+            [(Some("unused_variables"), "src/generated/mod.rs")]
+        );
+        assert_eq!(
+            visible(&filters(&["src/**"], &[], &[], &["unused"])),
+            [(Some("clippy::doc_markdown"), "src/lib.rs")]
+        );
+        assert_eq!(
+            visible(&filters(&[], &["src/generated/**"], &["unused"], &[])),
+            []
+        );
+        assert!(
+            Filters::new(&{
+                let mut options = Options::default();
+                options.exclude_lints = vec!["[".to_owned()];
+                options
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,397 @@
+use crate::models::{Fix, Location, Replacement};
+use anyhow::{Context, Result};
+use cargo_metadata::CompilerMessage;
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Write as _,
+    fs,
+    io::{BufRead, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+const CONTEXT_LINES: usize = 3;
+const PROMPT: &str = "Apply this fix [y,n,s,a,d,q,?]? ";
+const HELP: &str = "y - apply this fix
+n - do not apply this fix
+s - skip this fix for now, it will be asked again later
+a - apply this fix and all later fixes of the same lint
+d - do not apply this fix or any later fixes of the same lint
+q - quit; fixes applied so far are written
+";
+
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Machine-applicable suggestions of warnings, shown as diffs one by one.
+pub struct FixReview<'w> {
+    hunks: Vec<Hunk>,
+    sources: BTreeMap<PathBuf, String>,
+    workspace_root: &'w Path,
+    colored: bool,
+}
+
+struct Hunk {
+    lint: String,
+    message: String,
+    fix: Fix,
+}
+
+pub struct AppliedFixes {
+    pub fixes: usize,
+    pub files: usize,
+    pub conflicts: usize,
+    /// Fixes which don't match the files anymore, for instance after they were edited.
+    pub unresolved: usize,
+}
+
+impl<'w> FixReview<'w> {
+    pub fn new(
+        messages: Vec<(CompilerMessage, Location)>,
+        workspace_root: &'w Path,
+        colored: bool,
+    ) -> Result<Self> {
+        let mut hunks = Vec::<Hunk>::new();
+        for (message, location) in messages {
+            // Alternative suggestions of the same message may conflict with each other
+            let Some(fix) = location.fixes.into_iter().next() else {
+                continue;
+            };
+            // The same code is checked once per target
+            if hunks
+                .iter()
+                .any(|hunk| hunk.fix.replacements == fix.replacements)
+            {
+                continue;
+            }
+            let message = message.message;
+            hunks.push(Hunk {
+                lint: message
+                    .code
+                    .map_or_else(|| message.message.clone(), |code| code.code),
+                message: message.message,
+                fix,
+            });
+        }
+
+        let mut sources = BTreeMap::new();
+        for replacement in hunks.iter().flat_map(|hunk| &hunk.fix.replacements) {
+            if !sources.contains_key(&replacement.path) {
+                let source = fs::read_to_string(&replacement.path)
+                    .with_context(|| format!("failed to read {}", replacement.path.display()))?;
+                sources.insert(replacement.path.clone(), source);
+            }
+        }
+
+        Ok(Self {
+            hunks,
+            sources,
+            workspace_root,
+            colored,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Asks about every fix and returns the accepted ones.
+    pub fn run(&self, input: &mut impl BufRead, output: &mut impl Write) -> Result<Vec<Fix>> {
+        let mut accepted = Vec::new();
+        let mut lint_decisions = HashMap::<&str, bool>::new();
+        let mut queue = (0..self.hunks.len()).collect::<VecDeque<_>>();
+
+        while let Some(index) = queue.pop_front() {
+            let hunk = &self.hunks[index];
+            if let Some(&accept) = lint_decisions.get(hunk.lint.as_str()) {
+                if accept {
+                    accepted.push(hunk.fix.clone());
+                }
+                continue;
+            }
+
+            output.write_all(self.render(hunk).as_bytes())?;
+            loop {
+                output.write_all(PROMPT.as_bytes())?;
+                output.flush()?;
+                let mut answer = String::new();
+                if input.read_line(&mut answer)? == 0 {
+                    writeln!(output)?;
+                    return Ok(accepted);
+                }
+                match answer.trim() {
+                    "y" => accepted.push(hunk.fix.clone()),
+                    "n" => (),
+                    "s" => queue.push_back(index),
+                    "a" | "d" => {
+                        let accept = answer.trim() == "a";
+                        if accept {
+                            accepted.push(hunk.fix.clone());
+                        }
+                        lint_decisions.insert(&hunk.lint, accept);
+                    },
+                    "q" => return Ok(accepted),
+                    _ => {
+                        output.write_all(HELP.as_bytes())?;
+                        continue;
+                    },
+                }
+                break;
+            }
+        }
+
+        Ok(accepted)
+    }
+
+    /// Writes fixes into files, skipping the ones that overlap with previously applied ones
+    /// or don't match the sources.
+    pub fn write(&self, fixes: Vec<Fix>) -> Result<AppliedFixes> {
+        let mut edits = BTreeMap::<&Path, Vec<(Range<usize>, &str)>>::new();
+        let mut result = AppliedFixes {
+            fixes: 0,
+            files: 0,
+            conflicts: 0,
+            unresolved: 0,
+        };
+        for fix in &fixes {
+            let Some(ranges) = fix
+                .replacements
+                .iter()
+                .map(|replacement| {
+                    let source = self.sources.get(&replacement.path)?;
+                    Some((
+                        replacement.path.as_path(),
+                        byte_range(source, replacement, 1)?,
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()
+            else {
+                result.unresolved += 1;
+                continue;
+            };
+            let overlaps = ranges.iter().any(|(path, range)| {
+                edits.get(path).is_some_and(|edits| {
+                    edits
+                        .iter()
+                        .any(|(i, _)| i.start < range.end && range.start < i.end)
+                })
+            });
+            if overlaps {
+                result.conflicts += 1;
+                continue;
+            }
+            for ((path, range), replacement) in ranges.into_iter().zip(&fix.replacements) {
+                edits
+                    .entry(path)
+                    .or_default()
+                    .push((range, &replacement.text));
+            }
+            result.fixes += 1;
+        }
+
+        for (path, edits) in edits {
+            let mut source = self.sources[path].clone();
+            replace_ranges(&mut source, edits);
+            fs::write(path, source)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            result.files += 1;
+        }
+        Ok(result)
+    }
+
+    fn render(&self, hunk: &Hunk) -> String {
+        let mut result = String::new();
+        let _ = writeln!(
+            result,
+            "{}warning: {} [{}]{}",
+            self.color(BOLD),
+            hunk.message,
+            hunk.lint,
+            self.color(RESET)
+        );
+        let _ = writeln!(result, "help: {}", hunk.fix.message);
+
+        let mut replacements_by_paths = BTreeMap::<&Path, Vec<&Replacement>>::new();
+        for replacement in &hunk.fix.replacements {
+            replacements_by_paths
+                .entry(&replacement.path)
+                .or_default()
+                .push(replacement);
+        }
+        for (path, replacements) in replacements_by_paths {
+            let relative_path = path
+                .strip_prefix(self.workspace_root)
+                .unwrap_or(path)
+                .display();
+            let _ = writeln!(result, "{}--- {relative_path}", self.color(BOLD));
+            let _ = writeln!(result, "+++ {relative_path}{}", self.color(RESET));
+            if let Some(diff) = self.diff(&self.sources[path], &replacements) {
+                result.push_str(&diff);
+            }
+        }
+        result
+    }
+
+    // Unified diff of the changed lines with some context around them
+    fn diff(&self, source: &str, replacements: &[&Replacement]) -> Option<String> {
+        let lines = source.lines().collect::<Vec<_>>();
+        let first_line = replacements.iter().map(|i| i.line_start).min()?;
+        let last_line = replacements.iter().map(|i| i.line_end).max()?;
+        let context_start = first_line.saturating_sub(CONTEXT_LINES).max(1);
+        let context_end = (last_line + CONTEXT_LINES).min(lines.len());
+
+        let mut old = lines.get(context_start - 1..context_end)?.join("\n");
+        old.push('\n');
+        let mut new = old.clone();
+        let edits = replacements
+            .iter()
+            .map(|i| Some((byte_range(&old, i, context_start)?, i.text.as_str())))
+            .collect::<Option<Vec<_>>>()?;
+        replace_ranges(&mut new, edits);
+
+        let old = old.lines().collect::<Vec<_>>();
+        let new = new.lines().collect::<Vec<_>>();
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut result = String::new();
+        let _ = writeln!(
+            result,
+            "{}@@ -{context_start},{} +{context_start},{} @@{}",
+            self.color(CYAN),
+            old.len(),
+            new.len(),
+            self.color(RESET)
+        );
+        for line in &old[..prefix] {
+            let _ = writeln!(result, " {line}");
+        }
+        for line in &old[prefix..old.len() - suffix] {
+            let _ = writeln!(result, "{}-{line}{}", self.color(RED), self.color(RESET));
+        }
+        for line in &new[prefix..new.len() - suffix] {
+            let _ = writeln!(result, "{}+{line}{}", self.color(GREEN), self.color(RESET));
+        }
+        for line in &old[old.len() - suffix..] {
+            let _ = writeln!(result, " {line}");
+        }
+        Some(result)
+    }
+
+    fn color(&self, color: &'static str) -> &'static str {
+        if self.colored { color } else { "" }
+    }
+}
+
+// Converts 1-based line and character columns of text that starts from first_line
+fn byte_range(text: &str, replacement: &Replacement, first_line: usize) -> Option<Range<usize>> {
+    let offset = |line: usize, column: usize| {
+        let mut line_start = 0;
+        for (index, line_text) in text.split_inclusive('\n').enumerate() {
+            if index + first_line == line {
+                let line_text = line_text.trim_end_matches(['\r', '\n']);
+                return line_text
+                    .char_indices()
+                    .map(|(i, _)| i)
+                    .chain([line_text.len()])
+                    .nth(column.checked_sub(1)?)
+                    .map(|i| line_start + i);
+            }
+            line_start += line_text.len();
+        }
+        (line == text.split_inclusive('\n').count() + first_line && column == 1)
+            .then_some(text.len())
+    };
+    let start = offset(replacement.line_start, replacement.column_start)?;
+    let end = offset(replacement.line_end, replacement.column_end)?;
+    (start <= end).then_some(start..end)
+}
+
+fn replace_ranges(text: &mut String, mut edits: Vec<(Range<usize>, &str)>) {
+    edits.sort_by_key(|(range, _)| range.start);
+    for (range, replacement) in edits.into_iter().rev() {
+        text.replace_range(range, replacement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::env;
+
+    #[test]
+    fn review_and_write() -> Result<()> {
+        let workspace_root =
+            env::temp_dir().join(format!("cargo-limit-fix-review-{}", std::process::id()));
+        fs::create_dir_all(&workspace_root)?;
+        let path = workspace_root.join("main.rs");
+        let source = "use std::fs;\nuse std::io;\n\nfn main() {\n    let mut a = 1;\n    let mut \
+                      b = 2;\n    println!(\"{a}{b}\");\n}\n";
+        fs::write(&path, source)?;
+
+        let hunk = |lint: &str, line_start, column_start, line_end, column_end| Hunk {
+            lint: lint.to_owned(),
+            message: format!("{lint} message"),
+            fix: Fix {
+                message: format!("{lint} help"),
+                replacements: vec![Replacement {
+                    path: path.clone(),
+                    line_start,
+                    column_start,
+                    line_end,
+                    column_end,
+                    text: String::new(),
+                }],
+            },
+        };
+        let review = FixReview {
+            hunks: vec![
+                hunk("unused_imports", 1, 1, 2, 1),
+                hunk("unused_imports", 2, 1, 3, 1),
+                hunk("unused_mut", 5, 9, 5, 13),
+                hunk("unused_mut", 6, 9, 6, 13),
+            ],
+            sources: BTreeMap::from([(path.clone(), source.to_owned())]),
+            workspace_root: &workspace_root,
+            colored: false,
+        };
+
+        let mut output = Vec::new();
+        let mut fixes = review.run(&mut "s\nd\n?\ny\nq\n".as_bytes(), &mut output)?;
+        let output = String::from_utf8(output)?;
+        assert!(output.contains(
+            "--- main.rs\n+++ main.rs\n@@ -2,7 +2,7 @@\n use std::io;\n \n fn main() {\n-    let \
+             mut a = 1;\n+    let a = 1;\n"
+        ));
+        assert!(output.contains(HELP));
+
+        fixes.push(fixes[0].clone());
+        fixes.push(hunk("unused_mut", 42, 1, 42, 5).fix);
+        let applied = review.write(fixes)?;
+        assert_eq!(
+            (
+                applied.fixes,
+                applied.files,
+                applied.conflicts,
+                applied.unresolved
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            source.replacen("let mut a", "let a", 1)
+        );
+
+        fs::remove_dir_all(&workspace_root)?;
+        Ok(())
+    }
+}
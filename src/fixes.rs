@@ -0,0 +1,93 @@
+use crate::models::{Fix, Replacement};
+use cargo_metadata::diagnostic::{Applicability, Diagnostic, DiagnosticSpan};
+use std::path::Path;
+
+/// Suggestions which rustc considers safe to apply without a review, grouped by messages.
+pub fn machine_applicable(diagnostic: &Diagnostic, workspace_root: &Path) -> Vec<Fix> {
+    [diagnostic]
+        .into_iter()
+        .chain(&diagnostic.children)
+        .filter_map(|i| {
+            let replacements = i
+                .spans
+                .iter()
+                .filter(|span| {
+                    span.suggestion_applicability == Some(Applicability::MachineApplicable)
+                })
+                .filter_map(|span| replacement(span, workspace_root))
+                .collect::<Vec<_>>();
+            (!replacements.is_empty()).then(|| Fix {
+                message: i.message.clone(),
+                replacements,
+            })
+        })
+        .collect()
+}
+
+fn replacement(span: &DiagnosticSpan, workspace_root: &Path) -> Option<Replacement> {
+    Some(Replacement {
+        path: workspace_root.join(&span.file_name),
+        line_start: span.line_start,
+        column_start: span.column_start,
+        line_end: span.line_end,
+        column_end: span.column_end,
+        text: span.suggested_replacement.clone()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    // rustc --edition 2024 --crate-type lib --error-format=json lib.rs
+    const UNUSED_PARENS: &str = r#"{"$message_type":"diagnostic","message":"unnecessary parentheses around assigned value","code":{"code":"unused_parens","explanation":null},"level":"warning","spans":[{"file_name":"lib.rs","byte_start":32,"byte_end":33,"line_start":2,"line_end":2,"column_start":13,"column_end":14,"is_primary":true,"text":[{"text":"    let x = (1);","highlight_start":13,"highlight_end":14}],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null},{"file_name":"lib.rs","byte_start":34,"byte_end":35,"line_start":2,"line_end":2,"column_start":15,"column_end":16,"is_primary":true,"text":[{"text":"    let x = (1);","highlight_start":15,"highlight_end":16}],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[{"message":"`#[warn(unused_parens)]` (part of `#[warn(unused)]`) on by default","code":null,"level":"note","spans":[],"children":[],"rendered":null},{"message":"remove these parentheses","code":null,"level":"help","spans":[{"file_name":"lib.rs","byte_start":32,"byte_end":33,"line_start":2,"line_end":2,"column_start":13,"column_end":14,"is_primary":true,"text":[{"text":"    let x = (1);","highlight_start":13,"highlight_end":14}],"label":null,"suggested_replacement":"","suggestion_applicability":"MachineApplicable","expansion":null},{"file_name":"lib.rs","byte_start":34,"byte_end":35,"line_start":2,"line_end":2,"column_start":15,"column_end":16,"is_primary":true,"text":[{"text":"    let x = (1);","highlight_start":15,"highlight_end":16}],"label":null,"suggested_replacement":" ","suggestion_applicability":"MachineApplicable","expansion":null}],"children":[],"rendered":null}],"rendered":"warning: unnecessary parentheses around assigned value\n"}"#;
+    const UNUSED_VARIABLE: &str = r#"{"$message_type":"diagnostic","message":"unused variable: `y`","code":{"code":"unused_variables","explanation":null},"level":"warning","spans":[{"file_name":"lib.rs","byte_start":45,"byte_end":46,"line_start":3,"line_end":3,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let y = 2;","highlight_start":9,"highlight_end":10}],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[{"message":"`#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default","code":null,"level":"note","spans":[],"children":[],"rendered":null},{"message":"if this is intentional, prefix it with an underscore","code":null,"level":"help","spans":[{"file_name":"lib.rs","byte_start":45,"byte_end":46,"line_start":3,"line_end":3,"column_start":9,"column_end":10,"is_primary":true,"text":[{"text":"    let y = 2;","highlight_start":9,"highlight_end":10}],"label":null,"suggested_replacement":"_y","suggestion_applicability":"MachineApplicable","expansion":null}],"children":[],"rendered":null}],"rendered":"warning: unused variable: `y`\n"}"#;
+    const MISMATCHED_TYPES: &str = r#"{"$message_type":"diagnostic","message":"mismatched types","code":{"code":"E0308","explanation":null},"level":"error","spans":[{"file_name":"lib.rs","byte_start":110,"byte_end":111,"line_start":8,"line_end":8,"column_start":21,"column_end":22,"is_primary":true,"text":[{"text":"    let s: String = 1;","highlight_start":21,"highlight_end":22}],"label":"expected `String`, found integer","suggested_replacement":null,"suggestion_applicability":null,"expansion":null},{"file_name":"lib.rs","byte_start":101,"byte_end":107,"line_start":8,"line_end":8,"column_start":12,"column_end":18,"is_primary":false,"text":[{"text":"    let s: String = 1;","highlight_start":12,"highlight_end":18}],"label":"expected due to this","suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[{"message":"try using a conversion method","code":null,"level":"help","spans":[{"file_name":"lib.rs","byte_start":111,"byte_end":111,"line_start":8,"line_end":8,"column_start":22,"column_end":22,"is_primary":true,"text":[{"text":"    let s: String = 1;","highlight_start":22,"highlight_end":22}],"label":null,"suggested_replacement":".to_string()","suggestion_applicability":"MaybeIncorrect","expansion":null}],"children":[],"rendered":null}],"rendered":"error[E0308]: mismatched types\n"}"#;
+
+    fn fixes(raw_diagnostic: &str) -> Vec<Fix> {
+        let diagnostic: Diagnostic = serde_json::from_str(raw_diagnostic).unwrap();
+        machine_applicable(&diagnostic, Path::new("/workspace"))
+    }
+
+    fn replacement_in_lib(line: usize, columns: (usize, usize), text: &str) -> Replacement {
+        Replacement {
+            path: Path::new("/workspace/lib.rs").to_path_buf(),
+            line_start: line,
+            column_start: columns.0,
+            line_end: line,
+            column_end: columns.1,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn single_span() {
+        assert_eq!(
+            fixes(UNUSED_VARIABLE),
+            vec![Fix {
+                message: "if this is intentional, prefix it with an underscore".to_owned(),
+                replacements: vec![replacement_in_lib(3, (9, 10), "_y")],
+            }]
+        );
+    }
+
+    #[test]
+    fn multi_span() {
+        assert_eq!(
+            fixes(UNUSED_PARENS),
+            vec![Fix {
+                message: "remove these parentheses".to_owned(),
+                replacements: vec![
+                    replacement_in_lib(2, (13, 14), ""),
+                    replacement_in_lib(2, (15, 16), " "),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn skip_not_machine_applicable() {
+        assert_eq!(fixes(MISMATCHED_TYPES), Vec::new());
+    }
+}
@@ -1,18 +1,60 @@
-use anyhow::{Context, Result};
+#[cfg(unix)]
+use crate::pty::{self, PtyReader};
+use crate::stderr::{StderrOutput, StderrParser};
+#[cfg(unix)]
+use std::os::fd::OwnedFd;
 use std::{
-    io::{self, Write},
-    process::{Child, ChildStdout},
+    borrow::Cow,
+    io::{self, BufRead, PipeReader, PipeWriter, Read, Write},
+    process::Stdio,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
+const CLEAR_LINE: &str = "\r\x1b[K";
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
 #[derive(Clone)]
 pub struct FlushingWriter<W> {
     writer: W,
 }
 
+#[derive(Debug)]
+pub enum ChildOutputReader {
+    Pipe(PipeReader),
+    #[cfg(unix)]
+    Pty(PtyReader),
+}
+
+#[derive(Debug)]
+pub enum ChildOutputWriter {
+    Pipe(PipeWriter),
+    #[cfg(unix)]
+    Pty(OwnedFd),
+}
+
+/// Single line of stderr, which is overwritten by the next one.
+/// It's shared between threads, so that any other output clears it first.
+#[derive(Clone, Default)]
+pub struct StatusLine {
+    shown: Arc<AtomicBool>,
+}
+
+/// Writes non-JSON lines of child stdout, which are cargo's stderr merged into it.
+pub struct TextLineWriter<'buffers> {
+    stderr_writer: &'buffers mut FlushingWriter<io::Stderr>,
+    stderr_parser: &'buffers mut Option<StderrParser>,
+    status_line: &'buffers StatusLine,
+}
+
 pub struct Buffers {
-    child_stdout_reader: io::BufReader<ChildStdout>,
+    child_stdout_reader: io::BufReader<ChildOutputReader>,
     stdout_writer: FlushingWriter<io::Stdout>,
     stderr_writer: FlushingWriter<io::Stderr>,
+    stderr_parser: Option<StderrParser>,
+    status_line: StatusLine,
 }
 
 impl<W> FlushingWriter<W> {
@@ -33,43 +75,226 @@ impl<W: Write> Write for FlushingWriter<W> {
     }
 }
 
+impl Read for ChildOutputReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Pipe(pipe) => pipe.read(buf),
+            #[cfg(unix)]
+            Self::Pty(pty) => pty.read(buf),
+        }
+    }
+}
+
+impl ChildOutputWriter {
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(match self {
+            Self::Pipe(pipe) => Self::Pipe(pipe.try_clone()?),
+            #[cfg(unix)]
+            Self::Pty(pty) => Self::Pty(pty.try_clone()?),
+        })
+    }
+}
+
+impl From<ChildOutputWriter> for Stdio {
+    fn from(writer: ChildOutputWriter) -> Self {
+        match writer {
+            ChildOutputWriter::Pipe(pipe) => pipe.into(),
+            #[cfg(unix)]
+            ChildOutputWriter::Pty(pty) => pty.into(),
+        }
+    }
+}
+
+impl TextLineWriter<'_> {
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let line = format!("{line}\n");
+        match self.stderr_parser {
+            Some(stderr_parser) => stderr_parser.write_line(&line, self.stderr_writer),
+            None => {
+                self.status_line.clear(self.stderr_writer)?;
+                self.stderr_writer.write_all(line.as_bytes())
+            },
+        }
+    }
+}
+
+impl StatusLine {
+    pub fn show(&self, writer: &mut impl Write, line: &str) -> io::Result<()> {
+        // Wrapped line can't be overwritten
+        let width = terminal_width().saturating_sub(1);
+        let line = match line.char_indices().nth(width) {
+            Some((end, _)) => &line[..end],
+            None => line,
+        };
+        write!(writer, "{CLEAR_LINE}{line}")?;
+        self.shown.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn clear(&self, writer: &mut impl Write) -> io::Result<()> {
+        if self.shown.swap(false, Ordering::AcqRel) {
+            write!(writer, "{CLEAR_LINE}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Buffers {
-    pub fn new(child: &mut Child) -> Result<Self> {
-        let child_stdout_reader =
-            io::BufReader::new(child.stdout.take().context("cannot read stdout")?);
+    /// `stderr_parser` handles cargo's stderr when it's merged into stdout.
+    pub fn new(
+        child_stdout: ChildOutputReader,
+        stderr_parser: Option<StderrParser>,
+        status_line: StatusLine,
+    ) -> Self {
+        let child_stdout_reader = io::BufReader::new(child_stdout);
         let stdout_writer = FlushingWriter::new(io::stdout());
         let stderr_writer = FlushingWriter::new(io::stderr());
-        Ok(Self {
+        Self {
             child_stdout_reader,
             stdout_writer,
             stderr_writer,
-        })
+            stderr_parser,
+            status_line,
+        }
+    }
+
+    pub fn child_stdout_reader_and_text_line_writer(
+        &mut self,
+    ) -> (&mut io::BufReader<ChildOutputReader>, TextLineWriter<'_>) {
+        let text_line_writer = TextLineWriter {
+            stderr_writer: &mut self.stderr_writer,
+            stderr_parser: &mut self.stderr_parser,
+            status_line: &self.status_line,
+        };
+        (&mut self.child_stdout_reader, text_line_writer)
     }
 
-    pub fn map_child_stdout_reader<'this, T>(
-        &'this mut self,
-        f: impl FnOnce(&'this mut io::BufReader<ChildStdout>) -> T,
-    ) -> T {
-        f(&mut self.child_stdout_reader)
+    pub fn finish_stderr(&mut self) -> io::Result<StderrOutput> {
+        match self.stderr_parser.take() {
+            Some(stderr_parser) => stderr_parser.finish(&mut self.stderr_writer),
+            None => Ok(StderrOutput::default()),
+        }
     }
 
     pub fn write_to_stdout(&mut self, text: &str) -> io::Result<()> {
+        self.status_line.clear(&mut self.stderr_writer)?;
         std::write!(&mut self.stdout_writer, "{}", text)
     }
 
     pub fn writeln_to_stdout(&mut self, text: &str) -> io::Result<()> {
+        self.status_line.clear(&mut self.stderr_writer)?;
         std::writeln!(&mut self.stdout_writer, "{}", text)
     }
 
     pub fn write_to_stderr(&mut self, text: String) -> io::Result<()> {
+        self.status_line.clear(&mut self.stderr_writer)?;
         std::write!(&mut self.stderr_writer, "{}", text)
     }
 
     pub fn write_all_to_stderr(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.status_line.clear(&mut self.stderr_writer)?;
         self.stderr_writer.write_all(buf)
     }
 
     pub fn copy_from_child_stdout_reader_to_stdout_writer(&mut self) -> io::Result<u64> {
+        self.status_line.clear(&mut self.stderr_writer)?;
         io::copy(&mut self.child_stdout_reader, &mut self.stdout_writer)
     }
+
+    pub fn copy_lines_from_child_stdout_reader_to_stdout_writer(
+        &mut self,
+        on_line: impl FnMut(&str),
+    ) -> io::Result<()> {
+        self.status_line.clear(&mut self.stderr_writer)?;
+        copy_lines(
+            &mut self.child_stdout_reader,
+            &mut self.stdout_writer,
+            on_line,
+        )
+    }
+
+    pub fn filter_lines_from_child_stdout_reader_to_stdout_writer(
+        &mut self,
+        mut is_visible: impl FnMut(&str) -> bool,
+    ) -> io::Result<()> {
+        let mut line = Vec::new();
+        while self.child_stdout_reader.read_until(b'\n', &mut line)? > 0 {
+            if is_visible(&String::from_utf8_lossy(&line)) {
+                self.status_line.clear(&mut self.stderr_writer)?;
+                self.stdout_writer.write_all(&line)?;
+            }
+            line.clear();
+        }
+        Ok(())
+    }
+
+    pub fn discard_child_stdout(&mut self) -> io::Result<u64> {
+        io::copy(&mut self.child_stdout_reader, &mut io::sink())
+    }
+}
+
+/// Copies chunks as soon as they arrive, so progress of unfinished lines stays visible.
+pub fn copy_lines(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    mut on_line: impl FnMut(&str),
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        writer.write_all(chunk)?;
+
+        let mut rest = chunk;
+        while let Some(end) = rest.iter().position(|&byte| byte == b'\n') {
+            line.extend_from_slice(&rest[..=end]);
+            on_line(&String::from_utf8_lossy(&line));
+            line.clear();
+            rest = &rest[end + 1..];
+        }
+        line.extend_from_slice(rest);
+
+        let consumed = chunk.len();
+        reader.consume(consumed);
+    }
+    if !line.is_empty() {
+        on_line(&String::from_utf8_lossy(&line));
+    }
+    Ok(())
+}
+
+pub fn strip_ansi_escape_codes(line: &str) -> Cow<'_, str> {
+    const ESCAPE: char = '\x1b';
+    if !line.contains(ESCAPE) {
+        return Cow::Borrowed(line);
+    }
+
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != ESCAPE {
+            result.push(c);
+        } else if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    Cow::Owned(result)
+}
+
+fn terminal_width() -> usize {
+    #[cfg(unix)]
+    {
+        pty::terminal_width(&io::stderr()).unwrap_or(DEFAULT_TERMINAL_WIDTH)
+    }
+
+    #[cfg(not(unix))]
+    {
+        DEFAULT_TERMINAL_WIDTH
+    }
 }
@@ -5,61 +5,137 @@ pub mod env_vars;
 #[doc(hidden)]
 pub mod models;
 #[doc(hidden)]
+pub mod nvim_rpc;
+#[cfg(unix)]
+#[doc(hidden)]
+pub mod nvim_sockets;
+#[doc(hidden)]
 pub mod process;
+#[cfg(unix)]
+#[doc(hidden)]
+pub mod pty;
+#[doc(hidden)]
+pub mod uri;
 
+mod annotations;
+mod baseline;
+mod build_script;
 mod cargo_toml;
+mod config;
+mod filters;
+mod fix_review;
+mod fixes;
 mod io;
+mod libtest;
+mod linker;
 mod messages;
 mod options;
+mod panic;
+mod reports;
+mod sarif;
+mod stderr;
+mod test_summary;
+mod warning_budget;
+mod watch;
 
 pub use process::NO_EXIT_CODE;
 
-use crate::models::{EditorData, Location};
-use anyhow::{Context, Result};
-use cargo_metadata::{Message, MetadataCommand};
+use crate::models::{EditorData, Level, Location};
+use anyhow::{Context, Result, format_err};
+use baseline::Baseline;
+use cargo_metadata::{CompilerMessage, Message, Metadata, MetadataCommand};
+use fix_review::FixReview;
 use io::Buffers;
-use messages::{Messages, transform_and_process_messages};
-use options::Options;
+use libtest::LibtestOutput;
+use messages::{
+    Messages, ProcessedMessages, filter_late_warnings, lint_code, select_late_warnings,
+    transform_and_process_messages,
+};
+use options::{COLOR_ALWAYS, COLOR_NEVER, Options, TEST_SUBCOMMAND};
 use process::{CargoProcess, failed_to_execute_error_text};
 use std::{
-    io::Write,
+    env,
+    io::{IsTerminal, Write},
+    iter,
     path::Path,
     process::{Command, Stdio},
 };
+use stderr::StderrLocations;
+use test_summary::TestSummary;
+use watch::Watcher;
 
 pub const NVIM: &str = "nvim";
 
+const WARNING_BUDGET_EXCEEDED_EXIT_CODE: i32 = 1;
+
+const ADDITIONAL_OPTIONS: &str = include_str!("../additional_options.txt");
 const ADDITIONAL_ENVIRONMENT_VARIABLES: &str =
     include_str!("../additional_environment_variables.txt");
 
 #[doc(hidden)]
 pub fn run_cargo_filtered(current_exe: String) -> Result<i32> {
-    let workspace_root = MetadataCommand::new()
-        .no_deps()
-        .exec()
-        .ok()
-        .map(|m| m.workspace_root);
-    let workspace_root = workspace_root.as_ref().map(|w| w.as_ref());
-    let options = Options::from_os_env(current_exe, workspace_root)?;
+    let metadata = MetadataCommand::new().no_deps().exec();
+    let workspace_root = metadata.as_ref().ok().map(|m| m.workspace_root.as_ref());
+    let mut options = Options::from_os_env(current_exe, workspace_root)?;
+    if let Ok(metadata) = &metadata {
+        options.checks_part_of_workspace =
+            options.selects_part_of_workspace(metadata, &env::current_dir()?);
+    }
 
-    let mut cargo_process = CargoProcess::run(&options)?;
+    if options.reviews_fixes() {
+        let workspace_root = workspace_root.context("fixes can be reviewed only in a workspace")?;
+        return review_fixes(&options, workspace_root);
+    }
+
+    if !options.watch || options.help || options.version {
+        return run_cargo_filtered_once(&options, metadata.as_ref().ok());
+    }
+    match &metadata {
+        Ok(metadata) => {
+            Watcher::new(metadata)?.run(|| run_cargo_filtered_once(&options, Some(metadata)))
+        },
+        // Without metadata it's unknown which directories to watch
+        Err(err) => Err(format_err!(
+            "failed to watch workspace changes: {}",
+            err.to_string().trim_end()
+        )),
+    }
+}
+
+fn run_cargo_filtered_once(options: &Options, metadata: Option<&Metadata>) -> Result<i32> {
+    let workspace_root = metadata.map(|metadata| metadata.workspace_root.as_std_path());
+    let mut cargo_process = CargoProcess::run(options)?;
     let mut buffers = cargo_process.buffers()?;
+    let mut compiler_locations = Vec::new();
 
     let process_messages = |buffers: &mut Buffers,
-                            messages: Vec<Message>,
+                            messages: Vec<(CompilerMessage, Location)>,
                             locations_in_consistent_order: Vec<Location>,
                             workspace_root: &Path|
      -> Result<()> {
-        let messages = messages.into_iter();
-        if options.json_message_format {
-            for message in messages {
+        let machine_readable_stdout = options.json_message_format || options.sarif_message_format;
+        if options.github_annotations && !machine_readable_stdout {
+            annotations::write_github_annotations(buffers, &messages, workspace_root)?;
+        }
+        if let Some(path) = &options.gitlab_report
+            && !options.help
+            && !options.version
+        {
+            annotations::write_gitlab_report(path, &messages, workspace_root)?;
+        }
+        if options.sarif_message_format {
+            let log = sarif::log(&messages, workspace_root);
+            buffers.writeln_to_stdout(&serde_json::to_string(&log)?)?;
+        } else if options.json_message_format {
+            for (message, _) in messages {
+                let message = Message::CompilerMessage(message);
                 buffers.writeln_to_stdout(&serde_json::to_string(&message)?)?;
             }
         } else {
-            for message in messages.filter_map(|message| match message {
-                Message::CompilerMessage(compiler_message) => compiler_message.message.rendered,
-                _ => None,
-            }) {
+            for message in messages
+                .into_iter()
+                .filter_map(|(message, _)| message.message.rendered)
+            {
                 // linker messages may produce unnecessary new line
                 let message = match message.rsplit_once("\n          \n\n") {
                     Some((message, "")) => format!("{message}\n\n"),
@@ -68,29 +144,33 @@ pub fn run_cargo_filtered(current_exe: String) -> Result<i32> {
                 buffers.write_to_stderr(message)?;
             }
         }
+        // Compiler messages are sent right away, since `cargo llrun` may run for a long time
+        compiler_locations = locations_in_consistent_order.clone();
         open_affected_files_in_external_app(
             buffers,
             locations_in_consistent_order,
-            &options,
+            options,
             workspace_root,
         )
     };
 
     let mut parsed_messages =
-        Messages::parse_with_timeout_on_error(&mut buffers, Some(&cargo_process), &options)?;
+        Messages::parse_with_timeout_on_error(&mut buffers, Some(&cargo_process), options)?;
 
+    let processed;
+    let mut test_failures = Vec::new();
     let exit_code = if parsed_messages.child_killed {
         buffers.writeln_to_stdout("")?;
         let exit_code = cargo_process.wait()?;
         parsed_messages.merge(Messages::parse_with_timeout_on_error(
             &mut buffers,
             None,
-            &options,
+            options,
         )?);
-        transform_and_process_messages(
+        processed = transform_and_process_messages(
             &mut buffers,
             parsed_messages,
-            &options,
+            options,
             workspace_root,
             process_messages,
         )?;
@@ -98,18 +178,63 @@ pub fn run_cargo_filtered(current_exe: String) -> Result<i32> {
 
         exit_code
     } else {
-        transform_and_process_messages(
+        processed = transform_and_process_messages(
             &mut buffers,
             parsed_messages,
-            &options,
+            options,
             workspace_root,
             process_messages,
         )?;
-        buffers.copy_from_child_stdout_reader_to_stdout_writer()?;
+        test_failures = copy_test_output(&mut buffers, options, workspace_root)?;
         cargo_process.wait()?
     };
+    let exit_code = if exit_code == 0 && !processed.within_warning_budget {
+        WARNING_BUDGET_EXCEEDED_EXIT_CODE
+    } else {
+        exit_code
+    };
+
+    let mut stderr_output = cargo_process.stderr_output()?;
+    stderr_output.merge(buffers.finish_stderr()?);
+    if let Some(metadata) = metadata {
+        let workspace_root = metadata.workspace_root.as_std_path();
+        let StderrLocations {
+            build_script_failures,
+            panics,
+            build_script_warnings,
+        } = stderr_output.into_locations(metadata, &env::current_dir()?);
+        let processed = ProcessedMessages {
+            has_errors: processed.has_errors || !build_script_failures.is_empty(),
+            ..processed
+        };
+        let build_script_warnings =
+            select_late_warnings(build_script_warnings, processed, options, workspace_root)?;
+
+        // The second update replaces compiler locations, so they're merged with the late ones
+        let has_late_locations = !build_script_failures.is_empty()
+            || !panics.is_empty()
+            || !test_failures.is_empty()
+            || !build_script_warnings.is_empty();
+        if has_late_locations {
+            // Errors of every kind go first, so that the text editor jumps to them
+            let (compiler_errors, compiler_warnings): (Vec<_>, Vec<_>) = compiler_locations
+                .into_iter()
+                .partition(|location| matches!(location.level, Level::Error | Level::Ice));
+            let locations = compiler_errors
+                .into_iter()
+                .chain(build_script_failures)
+                .chain(panics)
+                .chain(test_failures)
+                .chain(compiler_warnings)
+                .chain(build_script_warnings)
+                .collect();
+            open_affected_files_in_external_app(&mut buffers, locations, options, workspace_root)?;
+        }
+    }
 
     if options.help {
+        buffers.writeln_to_stdout("")?;
+        buffers.write_to_stdout(ADDITIONAL_OPTIONS)?;
         buffers.writeln_to_stdout("")?;
         buffers.write_to_stdout(ADDITIONAL_ENVIRONMENT_VARIABLES)?;
     }
@@ -117,6 +242,193 @@ pub fn run_cargo_filtered(current_exe: String) -> Result<i32> {
     Ok(exit_code)
 }
 
+#[doc(hidden)]
+pub fn collect_editor_data(args: impl Iterator<Item = String>) -> Result<Option<EditorData>> {
+    const CURRENT_EXE: &str = "cargo-llcheck";
+
+    let Some(metadata) = MetadataCommand::new().no_deps().exec().ok() else {
+        return Ok(None);
+    };
+    let workspace_root = Some(metadata.workspace_root.as_std_path());
+    let mut options = Options::from_args(
+        CURRENT_EXE.to_owned(),
+        iter::once(CURRENT_EXE.to_owned()).chain(args),
+        workspace_root,
+    )?;
+    // Checks run on every save, so they must not write reports or the warnings budget
+    options.open_in_external_app = String::new();
+    options.github_annotations = false;
+    options.checkstyle_report = None;
+    options.junit_report = None;
+    options.gitlab_report = None;
+    options.warn_budget = None;
+    options.warn_budget_file = None;
+    options.warn_ratchet = false;
+
+    let mut cargo_process = CargoProcess::run(&options)?;
+    let mut buffers = cargo_process.buffers()?;
+
+    let mut parsed_messages =
+        Messages::parse_with_timeout_on_error(&mut buffers, Some(&cargo_process), &options)?;
+    if parsed_messages.child_killed {
+        cargo_process.wait()?;
+        parsed_messages.merge(Messages::parse_with_timeout_on_error(
+            &mut buffers,
+            None,
+            &options,
+        )?);
+    }
+
+    let mut editor_data = None;
+    transform_and_process_messages(
+        &mut buffers,
+        parsed_messages,
+        &options,
+        workspace_root,
+        |_, _, locations_in_consistent_order, workspace_root| {
+            editor_data = Some(EditorData::new(
+                workspace_root,
+                locations_in_consistent_order,
+            ));
+            Ok(())
+        },
+    )?;
+    buffers.discard_child_stdout()?;
+    cargo_process.wait()?;
+    cargo_process.stderr_output()?;
+
+    Ok(editor_data)
+}
+
+#[doc(hidden)]
+pub fn update_baseline(subcommand: &str, args: impl Iterator<Item = String>) -> Result<i32> {
+    let metadata = MetadataCommand::new().no_deps().exec()?;
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let current_exe = format!("cargo-l{subcommand}");
+    let mut options = Options::from_args(
+        current_exe.clone(),
+        iter::once(current_exe).chain(args),
+        Some(workspace_root),
+    )?;
+    options.open_in_external_app = String::new();
+    options.time_limit_after_error = None;
+
+    let mut cargo_process = CargoProcess::run(&options)?;
+    let mut buffers = cargo_process.buffers()?;
+    let messages =
+        Messages::parse_with_timeout_on_error(&mut buffers, Some(&cargo_process), &options)?;
+    buffers.discard_child_stdout()?;
+    let exit_code = cargo_process.wait()?;
+    let mut stderr_output = cargo_process.stderr_output()?;
+    stderr_output.merge(buffers.finish_stderr()?);
+
+    if messages.has_errors() {
+        return Err(format_err!(
+            "baseline is not updated: errors must be fixed first"
+        ));
+    }
+    let warnings = messages.into_warnings(&options, workspace_root)?;
+    let build_script_warnings = filter_late_warnings(
+        stderr_output
+            .into_locations(&metadata, &env::current_dir()?)
+            .build_script_warnings,
+        &options,
+        workspace_root,
+    )?;
+    let baseline = Baseline::new(
+        warnings
+            .iter()
+            .map(|(message, location)| (lint_code(message), location))
+            .chain(
+                build_script_warnings
+                    .iter()
+                    .map(|location| (None, location)),
+            ),
+        workspace_root,
+    );
+    let path = baseline.save(&options, workspace_root)?;
+    buffers.write_to_stderr(format!(
+        "{} warnings are saved to {}\n",
+        baseline.len(),
+        path.display()
+    ))?;
+
+    Ok(exit_code)
+}
+
+fn review_fixes(options: &Options, workspace_root: &Path) -> Result<i32> {
+    let mut cargo_process = CargoProcess::run(options)?;
+    let mut buffers = cargo_process.buffers()?;
+    let messages =
+        Messages::parse_with_timeout_on_error(&mut buffers, Some(&cargo_process), options)?;
+    buffers.discard_child_stdout()?;
+    let exit_code = cargo_process.wait()?;
+    cargo_process.stderr_output()?;
+
+    if messages.has_errors() {
+        return Err(format_err!(
+            "fixes are not reviewed: errors must be fixed first"
+        ));
+    }
+    let warnings = messages.into_warnings(options, workspace_root)?;
+    let colored = options.color == COLOR_ALWAYS
+        || (options.color != COLOR_NEVER && std::io::stdout().is_terminal());
+    let review = FixReview::new(warnings, workspace_root, colored)?;
+    if review.is_empty() {
+        buffers.write_to_stderr("no fixes to review\n".to_owned())?;
+        return Ok(exit_code);
+    }
+
+    let fixes = review.run(&mut std::io::stdin().lock(), &mut std::io::stdout())?;
+    let applied = review.write(fixes)?;
+    buffers.write_to_stderr(format!(
+        "{} fixes are applied to {} files\n",
+        applied.fixes, applied.files
+    ))?;
+    if applied.conflicts > 0 {
+        buffers.write_to_stderr(format!(
+            "{} fixes are skipped because they overlap with other ones\n",
+            applied.conflicts
+        ))?;
+    }
+    if applied.unresolved > 0 {
+        buffers.write_to_stderr(format!(
+            "{} fixes are skipped because they don't match the files anymore\n",
+            applied.unresolved
+        ))?;
+    }
+
+    Ok(exit_code)
+}
+
+/// Returns locations of test failures.
+fn copy_test_output(
+    buffers: &mut Buffers,
+    options: &Options,
+    workspace_root: Option<&Path>,
+) -> Result<Vec<Location>> {
+    let Some(workspace_root) = workspace_root.filter(|_| options.subcommand() == TEST_SUBCOMMAND)
+    else {
+        buffers.copy_from_child_stdout_reader_to_stdout_writer()?;
+        return Ok(Vec::new());
+    };
+
+    let mut libtest_output = LibtestOutput::default();
+    if options.shows_test_summary() {
+        let mut test_summary = TestSummary::default();
+        buffers.filter_lines_from_child_stdout_reader_to_stdout_writer(|line| {
+            libtest_output.push_line(line);
+            test_summary.is_visible(line)
+        })?;
+        test_summary.write(buffers)?;
+    } else {
+        buffers.copy_lines_from_child_stdout_reader_to_stdout_writer(|line| {
+            libtest_output.push_line(line)
+        })?;
+    }
+    Ok(libtest_output.into_locations(workspace_root))
+}
+
 fn open_affected_files_in_external_app(
     buffers: &mut Buffers,
     locations_in_consistent_order: Vec<Location>,
@@ -0,0 +1,165 @@
+use crate::{
+    io::strip_ansi_escape_codes,
+    models::{Level, Location},
+    panic::{Panic, PanicParser},
+};
+use serde::Deserialize;
+use std::path::Path;
+
+pub(crate) const TEST_OUTPUT_HEADER_PREFIX: &str = "---- ";
+const TEST_OUTPUT_HEADER_SUFFIXES: &[&str] = &[" stdout ----", " stderr ----"];
+
+#[derive(Default)]
+pub struct LibtestOutput {
+    current_test: Option<String>,
+    panic_parser: PanicParser,
+    failures: Vec<(Option<String>, Panic)>,
+}
+
+// Emitted by `cargo test -- -Z unstable-options --format json`
+#[derive(Deserialize)]
+struct TestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    name: String,
+    event: String,
+    stdout: Option<String>,
+}
+
+impl LibtestOutput {
+    pub fn push_line(&mut self, line: &str) {
+        let line = strip_ansi_escape_codes(line.trim_end_matches(['\r', '\n']));
+        if let Some(event) = TestEvent::parse(&line) {
+            if event.kind == "test" && event.event == "failed" {
+                self.flush();
+                self.current_test = Some(event.name);
+                for line in event.stdout.as_deref().unwrap_or_default().lines() {
+                    self.panic_parser.push_line(line);
+                }
+                self.flush();
+            }
+        } else if let Some(test) = parse_test_output_header(&line) {
+            self.flush();
+            self.current_test = Some(test.to_owned());
+        } else {
+            self.panic_parser.push_line(&line);
+        }
+    }
+
+    pub fn into_locations(mut self, workspace_root: &Path) -> Vec<Location> {
+        self.flush();
+        self.failures
+            .into_iter()
+            .filter_map(|(test, panic)| {
+                let path = panic.resolve_path(workspace_root)?;
+                let test = test.or(panic.thread).unwrap_or_default();
+                Some(Location {
+                    path,
+                    line: panic.line,
+                    column: panic.column,
+                    message: format!("test {test} failed: {}", panic.message),
+                    level: Level::TestFailure,
+                    label: None,
+                    related: Vec::new(),
+                    fixes: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    fn flush(&mut self) {
+        let panics = std::mem::take(&mut self.panic_parser).finish();
+        let test = self.current_test.take();
+        self.failures
+            .extend(panics.into_iter().map(|panic| (test.clone(), panic)));
+    }
+}
+
+impl TestEvent {
+    fn parse(line: &str) -> Option<Self> {
+        if line.starts_with('{') {
+            serde_json::from_str(line).ok()
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_test_output_header(line: &str) -> Option<&str> {
+    let line = line.strip_prefix(TEST_OUTPUT_HEADER_PREFIX)?;
+    TEST_OUTPUT_HEADER_SUFFIXES
+        .iter()
+        .find_map(|suffix| line.strip_suffix(suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn plain_and_json_output() {
+        let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let output = r#"running 3 tests
+test tests::a ... FAILED
+failures:
+
+---- tests::a stdout ----
+
+thread 'tests::a' (10820) panicked at src/lib.rs:4:14:
+assertion `left == right` failed
+  left: 2
+ right: 3
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+---- tests::b stdout ----
+thread 'tests::b' panicked at 'explicit panic', src/models.rs:6:5
+{ "type": "test", "name": "tests::c", "event": "failed", "stdout": "thread 'tests::c' panicked at src/io.rs:1:2:\nboom\n" }
+thread 'tests::d' panicked at /rustc/library/core/src/option.rs:1:2:
+outside of workspace
+"#;
+
+        let mut libtest_output = LibtestOutput::default();
+        for line in output.lines() {
+            libtest_output.push_line(line);
+        }
+        let locations = libtest_output
+            .into_locations(workspace_root)
+            .into_iter()
+            .map(|location| {
+                let path = location.path.strip_prefix(workspace_root).unwrap();
+                (
+                    path.to_owned(),
+                    location.line,
+                    location.column,
+                    location.message,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations,
+            vec![
+                (
+                    "src/lib.rs".into(),
+                    4,
+                    14,
+                    "test tests::a failed: assertion `left == right` failed\n  left: 2\n right: 3"
+                        .to_owned()
+                ),
+                (
+                    "src/models.rs".into(),
+                    6,
+                    5,
+                    "test tests::b failed: explicit panic".to_owned()
+                ),
+                (
+                    "src/io.rs".into(),
+                    1,
+                    2,
+                    "test tests::c failed: boom".to_owned()
+                ),
+            ]
+        );
+    }
+}
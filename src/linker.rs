@@ -0,0 +1,270 @@
+use crate::panic::parse_location;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const GNU_LD_FUNCTION_PREFIX: &str = ": in function `";
+const GNU_LD_UNDEFINED_REFERENCE: &str = ": undefined reference to `";
+const LLD_UNDEFINED_SYMBOL: &str = ": error: undefined symbol: ";
+const LLD_REFERENCE_PREFIX: &str = ">>> ";
+const LLD_REFERENCED_BY_PREFIX: &str = "referenced by ";
+const MSVC_UNRESOLVED_SYMBOL: &str = ": error LNK2019: unresolved external symbol ";
+const MSVC_FUNCTION_PREFIX: &str = " referenced in function ";
+
+const PARSERS: &[&dyn LinkerParser] = &[&GnuLd, &Lld, &Msvc];
+
+/// Place of code that refers to an unresolved symbol.
+#[derive(Debug, Default, PartialEq)]
+pub struct Reference {
+    pub location: Option<(PathBuf, usize)>,
+    pub function: Option<String>,
+}
+
+trait LinkerParser {
+    fn parse(&self, output: &str) -> Vec<Reference>;
+}
+
+// /usr/bin/ld: /path/to/app.o: in function `app::main':
+// /path/to/src/main.rs:6: undefined reference to `symbol'
+struct GnuLd;
+
+// rust-lld, ld.lld, lld-link, mold:
+// rust-lld: error: undefined symbol: symbol
+// >>> referenced by main.rs:6 (src/main.rs:6)
+// >>>               /path/to/app.o:(app::main)
+struct Lld;
+
+// app.o : error LNK2019: unresolved external symbol symbol referenced in function _ZN3app4main17h0123456789abcdefE
+struct Msvc;
+
+/// Finds references to unresolved symbols in linker output, regardless of the linker.
+pub fn parse_references(output: &str) -> Vec<Reference> {
+    PARSERS
+        .iter()
+        .flat_map(|parser| parser.parse(output))
+        .collect()
+}
+
+impl Reference {
+    /// Returns path relative to workspace root and line number of the reference.
+    /// Linkers may not know source locations (for instance, without debug info), so in that case
+    /// the function is looked up in module files of the target.
+    pub fn resolve(&self, src_path: &Path, workspace_root: &Path) -> Option<(PathBuf, usize)> {
+        let in_workspace = |path: &Path| {
+            let path = workspace_root.join(path).components().collect::<PathBuf>();
+            let relative = path.strip_prefix(workspace_root).ok()?.to_owned();
+            path.is_file().then_some(relative)
+        };
+        if let Some((path, line)) = &self.location
+            && let Some(path) = in_workspace(path)
+        {
+            return Some((path, *line));
+        }
+
+        let (modules, function) = split_function_path(self.function.as_deref()?)?;
+        let src_dir = src_path.parent()?;
+        let mut candidates = (1..=modules.len())
+            .rev()
+            .flat_map(|len| {
+                let module = modules[..len].iter().collect::<PathBuf>();
+                [
+                    src_dir.join(&module).with_extension("rs"),
+                    src_dir.join(&module).join("mod.rs"),
+                ]
+            })
+            .collect::<Vec<_>>();
+        candidates.push(src_path.to_owned());
+        candidates.into_iter().find_map(|path| {
+            let line = find_function(&fs::read_to_string(&path).ok()?, &function)?;
+            Some((in_workspace(&path)?, line))
+        })
+    }
+}
+
+impl LinkerParser for GnuLd {
+    fn parse(&self, output: &str) -> Vec<Reference> {
+        let mut result = Vec::new();
+        let mut function = None;
+        for line in output.lines() {
+            if let Some((_, name)) = line.split_once(GNU_LD_FUNCTION_PREFIX) {
+                function = name.strip_suffix("':").map(demangle);
+            } else if let Some((location, _)) = line.split_once(GNU_LD_UNDEFINED_REFERENCE) {
+                result.push(Reference {
+                    location: parse_location(location).map(|(path, line, _)| (path, line)),
+                    function: function.clone(),
+                });
+            }
+        }
+        result
+    }
+}
+
+impl LinkerParser for Lld {
+    fn parse(&self, output: &str) -> Vec<Reference> {
+        let mut result = Vec::new();
+        let mut current: Option<Reference> = None;
+        for line in output.lines() {
+            let Some(reference) = line.strip_prefix(LLD_REFERENCE_PREFIX) else {
+                result.extend(current.take());
+                continue;
+            };
+            let reference = reference.trim_start();
+            if let Some(location) = reference.strip_prefix(LLD_REFERENCED_BY_PREFIX) {
+                result.extend(current.take());
+                // Relative to the current directory of rustc, if present: main.rs:6 (src/main.rs:6)
+                let location = location
+                    .strip_suffix(')')
+                    .and_then(|location| location.rsplit_once(" ("))
+                    .map_or(location, |(_, location)| location);
+                current = Some(Reference {
+                    location: parse_location(location).map(|(path, line, _)| (path, line)),
+                    function: None,
+                });
+            } else if let Some(current) = &mut current
+                && current.function.is_none()
+            {
+                current.function = reference
+                    .strip_suffix(')')
+                    .and_then(|reference| reference.rsplit_once(":("))
+                    .map(|(_, function)| demangle(function));
+            }
+        }
+        result.extend(current);
+        // Prefix is tool specific, so references are taken into account only after the error
+        if output.contains(LLD_UNDEFINED_SYMBOL) {
+            result
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl LinkerParser for Msvc {
+    fn parse(&self, output: &str) -> Vec<Reference> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (_, symbol) = line.split_once(MSVC_UNRESOLVED_SYMBOL)?;
+                let (_, function) = symbol.split_once(MSVC_FUNCTION_PREFIX)?;
+                Some(Reference {
+                    location: None,
+                    function: Some(demangle(function.trim_end())),
+                })
+            })
+            .collect()
+    }
+}
+
+// Both legacy and v0 symbols, without hashes
+fn demangle(symbol: &str) -> String {
+    let demangled = rustc_demangle::try_demangle(symbol)
+        .map(|demangled| format!("{demangled:#}"))
+        .unwrap_or_else(|_| symbol.to_owned());
+    // Some linkers demangle legacy symbols themselves, keeping hashes: app::main::h0123456789abcdef
+    match demangled.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            path.to_owned()
+        },
+        _ => demangled,
+    }
+}
+
+// "app::module::Type::method" -> (["module", "Type"], "method")
+// "<app::module::Type as core::fmt::Debug>::fmt" -> (["module", "Type"], "fmt")
+fn split_function_path(path: &str) -> Option<(Vec<String>, String)> {
+    let path = match path.strip_prefix('<') {
+        Some(qualified) => {
+            let (self_type, rest) = qualified.split_once(">::")?;
+            let self_type = self_type.split_once(" as ").map_or(self_type, |(i, _)| i);
+            format!("{self_type}::{rest}")
+        },
+        None => path.to_owned(),
+    };
+
+    let mut depth = 0usize;
+    let path = path
+        .chars()
+        .filter(|c| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth = depth.saturating_sub(1),
+                _ => return depth == 0,
+            }
+            false
+        })
+        .collect::<String>();
+    let mut segments = path
+        .split("::")
+        .filter(|segment| !segment.is_empty() && !segment.starts_with('{'))
+        .skip(1)
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    let function = segments.pop()?;
+    Some((segments, function))
+}
+
+fn find_function(source: &str, function: &str) -> Option<usize> {
+    let definition = format!("fn {function}");
+    source
+        .lines()
+        .position(|line| {
+            line.split_once(&definition).is_some_and(|(before, after)| {
+                (before.is_empty() || before.ends_with(' ')) && after.starts_with(['(', '<', ' '])
+            })
+        })
+        .map(|index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn linkers() {
+        let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/stubs/linker_error");
+        let src_path = workspace_root.join("src/main.rs");
+        let fixtures_dir = workspace_root.join("linkers");
+        let main_rs = PathBuf::from("src/main.rs");
+        let trigger = Some("linker_error::trigger".to_owned());
+
+        for (linker, location, resolved_line) in [
+            ("gnu_ld", Some(("/tmp/linker_error/src/main.rs", 6)), 5),
+            ("lld", Some(("src/main.rs", 6)), 6),
+            ("mold", None, 5),
+            ("msvc", None, 5),
+        ] {
+            let output =
+                fs::read_to_string(fixtures_dir.join(linker).with_extension("txt")).unwrap();
+            let references = parse_references(&output);
+            assert_eq!(
+                references,
+                vec![Reference {
+                    location: location.map(|(path, line)| (PathBuf::from(path), line)),
+                    function: trigger.clone(),
+                }],
+                "{linker}"
+            );
+            assert_eq!(
+                references[0].resolve(&src_path, &workspace_root),
+                Some((main_rs.clone(), resolved_line)),
+                "{linker}"
+            );
+        }
+    }
+
+    #[test]
+    fn function_paths() {
+        let split = |path| split_function_path(path).unwrap();
+        assert_eq!(split("app::main"), (vec![], "main".to_owned()));
+        assert_eq!(
+            split("<app::a::Foo<u8> as core::fmt::Debug>::fmt"),
+            (vec!["a".to_owned(), "Foo".to_owned()], "fmt".to_owned())
+        );
+        assert_eq!(
+            split("app::a::run::{closure#0}"),
+            (vec!["a".to_owned()], "run".to_owned())
+        );
+    }
+}
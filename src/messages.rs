@@ -1,4 +1,13 @@
-use crate::{io::Buffers, models::Location, options::Options, process};
+use crate::{
+    baseline::Baseline,
+    filters::{Filters, LintGroups},
+    io::Buffers,
+    linker,
+    models::{Level, Location},
+    options::Options,
+    process, reports,
+    warning_budget::WarningBudget,
+};
 use anyhow::Result;
 use cargo_metadata::{
     CompilerMessage, Message,
@@ -18,14 +27,24 @@ pub struct Messages {
     pub child_killed: bool,
 }
 
+/// What was shown of compiler messages: the late ones from stderr follow the same rules.
+#[derive(Clone, Copy, Debug)]
+pub struct ProcessedMessages {
+    pub within_warning_budget: bool,
+    pub has_errors: bool,
+    pub shown_messages: usize,
+}
+
 struct FilteredAndOrderedMessages {
     errors: Vec<(CompilerMessage, Location)>,
     warnings: Vec<(CompilerMessage, Location)>,
 }
 
 struct TransformedMessages {
-    messages: Vec<Message>,
+    messages: Vec<(CompilerMessage, Location)>,
     locations_in_consistent_order: Vec<Location>,
+    warning_budget: WarningBudget,
+    has_errors: bool,
 }
 
 #[derive(Clone, PartialEq, PartialOrd, Ord, Eq, Hash)]
@@ -40,21 +59,82 @@ pub fn transform_and_process_messages(
     messages: Messages,
     options: &Options,
     workspace_root: Option<&Path>,
-    mut process: impl FnMut(&mut Buffers, Vec<Message>, Vec<Location>, &Path) -> Result<()>,
-) -> Result<()> {
+    mut process: impl FnMut(
+        &mut Buffers,
+        Vec<(CompilerMessage, Location)>,
+        Vec<Location>,
+        &Path,
+    ) -> Result<()>,
+) -> Result<ProcessedMessages> {
     if let Some(workspace_root) = workspace_root {
         let TransformedMessages {
             messages,
             locations_in_consistent_order,
+            warning_budget,
+            has_errors,
         } = TransformedMessages::transform(messages, options, workspace_root)?;
+        let shown_messages = messages.len();
         process(
             buffers,
             messages,
             locations_in_consistent_order,
             workspace_root,
         )?;
+        Ok(ProcessedMessages {
+            within_warning_budget: warning_budget.check(buffers, options, workspace_root)?,
+            has_errors,
+            shown_messages,
+        })
+    } else {
+        Ok(ProcessedMessages {
+            within_warning_budget: true,
+            has_errors: messages.has_errors(),
+            shown_messages: 0,
+        })
     }
-    Ok(())
+}
+
+/// Warnings which come from cargo's stderr (build script ones) are known only after compiler
+/// messages are processed; they pass the same path and lint filters as rustc warnings.
+pub fn filter_late_warnings(
+    warnings: Vec<Location>,
+    options: &Options,
+    workspace_root: &Path,
+) -> Result<Vec<Location>> {
+    let filters = Filters::new(options)?;
+    let lint_groups = LintGroups::default();
+    Ok(warnings
+        .into_iter()
+        .filter(|location| filters.is_visible(None, location, &lint_groups, workspace_root))
+        .collect())
+}
+
+/// Late warnings for the text editor: filtered, not in the baseline, only if there are no errors
+/// (unless they're always shown) and within what's left of the messages limit.
+pub fn select_late_warnings(
+    warnings: Vec<Location>,
+    processed: ProcessedMessages,
+    options: &Options,
+    workspace_root: &Path,
+) -> Result<Vec<Location>> {
+    let hidden_by_errors = processed.has_errors && !options.show_warnings_if_errors_exist;
+    if !options.open_in_external_app_on_warnings || hidden_by_errors {
+        return Ok(Vec::new());
+    }
+
+    let warnings = filter_late_warnings(warnings, options, workspace_root)?;
+    let warnings =
+        Baseline::load(options, workspace_root)?
+            .filter(warnings, workspace_root, |location| (None, location));
+    let limit = match options.limit_messages {
+        0 => usize::MAX,
+        limit => limit.saturating_sub(processed.shown_messages),
+    };
+    Ok(warnings.into_iter().take(limit).collect())
+}
+
+pub fn lint_code(message: &CompilerMessage) -> Option<&str> {
+    message.message.code.as_ref().map(|code| code.code.as_str())
 }
 
 impl Messages {
@@ -68,7 +148,9 @@ impl Messages {
             return Ok(result);
         }
 
-        for message in buffers.map_child_stdout_reader(Message::parse_stream) {
+        let (child_stdout_reader, mut text_line_writer) =
+            buffers.child_stdout_reader_and_text_line_writer();
+        for message in Message::parse_stream(child_stdout_reader) {
             match message? {
                 Message::CompilerMessage(compiler_message) => {
                     match compiler_message.message.level {
@@ -82,6 +164,7 @@ impl Messages {
                 Message::BuildFinished(_) => {
                     break;
                 },
+                Message::TextLine(line) => text_line_writer.write_line(&line)?,
                 _ => (),
             }
 
@@ -110,20 +193,34 @@ impl Messages {
         self.child_killed |= other.child_killed;
     }
 
-    fn has_errors(&self) -> bool {
+    pub fn into_warnings(
+        self,
+        options: &Options,
+        workspace_root: &Path,
+    ) -> Result<Vec<(CompilerMessage, Location)>> {
+        Ok(FilteredAndOrderedMessages::filter(self, options, workspace_root)?.warnings)
+    }
+
+    pub fn has_errors(&self) -> bool {
         !self.errors.is_empty() || !self.internal_compiler_errors.is_empty()
     }
 }
 
 impl FilteredAndOrderedMessages {
-    fn filter(messages: Messages, options: &Options, workspace_root: &Path) -> Self {
+    fn filter(messages: Messages, options: &Options, workspace_root: &Path) -> Result<Self> {
+        let filters = Filters::new(options)?;
+        let lint_groups = LintGroups::parse(&messages.non_errors);
+
         let non_errors = messages.non_errors.into_iter();
         let warnings = if options.show_dependencies_warnings {
             Either::Left(non_errors)
         } else {
             Either::Right(non_errors.filter(|i| i.target.src_path.starts_with(workspace_root)))
         };
-        let warnings = Self::filter_and_order_messages(warnings, workspace_root);
+        let mut warnings = Self::filter_and_order_messages(warnings, workspace_root);
+        warnings.retain(|(message, location)| {
+            filters.is_visible(lint_code(message), location, &lint_groups, workspace_root)
+        });
 
         let errors = messages
             .internal_compiler_errors
@@ -131,7 +228,7 @@ impl FilteredAndOrderedMessages {
             .chain(messages.errors);
         let errors = Self::filter_and_order_messages(errors, workspace_root);
 
-        Self { errors, warnings }
+        Ok(Self { errors, warnings })
     }
 
     fn filter_and_order_messages(
@@ -183,22 +280,28 @@ fn parse_incomplete_message(
     i: &CompilerMessage,
     workspace_root: &Path,
 ) -> Result<(SpanKey, DiagnosticSpan)> {
-    let is_project_file = i.target.src_path.starts_with(workspace_root);
-    let path = if is_project_file {
-        i.target.src_path.strip_prefix(workspace_root)?.to_string()
-    } else {
-        i.target.src_path.to_string()
-    };
-
-    let line = i
+    let src_path = i.target.src_path.as_std_path();
+    let linker_output = i
         .message
         .children
         .iter()
-        .find(|child| child.message.starts_with("rust-lld: error:"))
-        .and_then(|child| child.message.rsplit_once(&format!("({path}:")))
-        .and_then(|(_, end)| end.split_once(")\n"))
-        .and_then(|(line, _)| line.parse().ok())
-        .unwrap_or(1usize);
+        .map(|child| child.message.as_str())
+        .join("\n");
+    let reference = linker::parse_references(&linker_output)
+        .iter()
+        .find_map(|reference| reference.resolve(src_path, workspace_root));
+    let (path, line) = match reference {
+        Some((path, line)) => (path.to_string_lossy().to_string(), line),
+        None => {
+            let is_project_file = src_path.starts_with(workspace_root);
+            let path = if is_project_file {
+                i.target.src_path.strip_prefix(workspace_root)?.to_string()
+            } else {
+                i.target.src_path.to_string()
+            };
+            (path, 1)
+        },
+    };
 
     let ignored_span_values = DiagnosticSpanBuilder::default()
         .byte_start(0u32)
@@ -231,8 +334,25 @@ impl TransformedMessages {
         workspace_root: &Path,
     ) -> Result<TransformedMessages> {
         let FilteredAndOrderedMessages { errors, warnings } =
-            FilteredAndOrderedMessages::filter(messages, options, workspace_root);
+            FilteredAndOrderedMessages::filter(messages, options, workspace_root)?;
+        let warnings = Baseline::load(options, workspace_root)?.filter(
+            warnings,
+            workspace_root,
+            |(message, location)| (lint_code(message), location),
+        );
+        reports::write(&errors, &warnings, options, workspace_root)?;
         let has_errors = !errors.is_empty();
+        let warning_budget = WarningBudget {
+            unique_workspace_warnings: warnings
+                .iter()
+                .filter(|(message, _)| {
+                    message.message.level == DiagnosticLevel::Warning
+                        && message.target.src_path.starts_with(workspace_root)
+                })
+                .count(),
+            has_errors,
+            checks_part_of_workspace: options.checks_part_of_workspace,
+        };
 
         let errors = errors.into_iter();
         let warnings = warnings.into_iter();
@@ -248,22 +368,23 @@ impl TransformedMessages {
 
         let limit_messages = options.limit_messages;
         let no_limit = limit_messages == 0;
-        let (messages, locations): (Vec<_>, Vec<_>) = {
+        let messages: Vec<_> = {
             if no_limit {
                 Either::Left(messages)
             } else {
                 Either::Right(messages.take(limit_messages))
             }
         }
-        .unzip();
+        .collect();
 
-        let locations_in_consistent_order = locations
-            .into_iter()
+        let locations_in_consistent_order = messages
+            .iter()
+            .map(|(_, location)| location.clone())
             .filter(|i| {
                 if options.open_in_external_app_on_warnings {
                     true
                 } else {
-                    matches!(i.level, DiagnosticLevel::Error | DiagnosticLevel::Ice)
+                    matches!(i.level, Level::Error | Level::Ice)
                 }
             })
             .collect();
@@ -276,12 +397,13 @@ impl TransformedMessages {
                 Either::Right(messages.rev())
             }
         }
-        .map(Message::CompilerMessage)
         .collect();
 
         Ok(Self {
             messages,
             locations_in_consistent_order,
+            warning_budget,
+            has_errors,
         })
     }
 }
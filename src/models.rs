@@ -1,31 +1,81 @@
+use crate::fixes;
 use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+/// Version of `EditorData` format, it's changed only when the format changes.
+pub const PROTOCOL_VERSION: &str = "0.0.14";
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct EditorData {
     pub protocol_version: String,
     pub workspace_root: PathBuf,
     pub locations: Vec<Location>,
-    pub corrected_locations: u8, // It's actually a bool; however, it's hard to properly serialize it
-                                 // as a VimScript {expr} (where bools are non-JSON v:true/v:false)
-                                 // without breaking something else
-                                 // (specifically workspace_root string escaping before json_decode)
+    // It's actually a bool; msgpack-RPC would pass it as v:true/v:false,
+    // but it stays a number to keep the format of custom handlers unchanged
+    pub corrected_locations: u8,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Location {
     pub path: PathBuf,
     pub line: usize,
     pub column: usize,
     pub message: String,
-    pub level: DiagnosticLevel,
+    pub level: Level,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedLocation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<Fix>,
+}
+
+/// Other span of the same message, like "first borrow occurs here".
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RelatedLocation {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Level {
+    #[serde(rename = "error: internal compiler error")]
+    Ice,
+    Error,
+    Warning,
+    FailureNote,
+    Note,
+    Help,
+    TestFailure,
+}
+
+/// Machine-applicable suggestion, like the ones `cargo fix` applies.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: String,
+    pub replacements: Vec<Replacement>,
+}
+
+/// Columns are 1-based and count characters, like in rustc's spans; end is exclusive.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub path: PathBuf,
+    pub line_start: usize,
+    pub column_start: usize,
+    pub line_end: usize,
+    pub column_end: usize,
+    pub text: String,
 }
 
 impl EditorData {
     pub fn new(workspace_root: &Path, locations_in_consistent_order: Vec<Location>) -> Self {
         let workspace_root = workspace_root.to_path_buf();
-        let protocol_version = std::env!("CARGO_PKG_VERSION").to_string();
+        let protocol_version = PROTOCOL_VERSION.to_owned();
         Self {
             protocol_version,
             workspace_root,
@@ -44,18 +94,57 @@ impl EditorData {
 
 impl Location {
     pub fn new(span: DiagnosticSpan, diagnostic: &Diagnostic, workspace_root: &Path) -> Self {
-        let path = PathBuf::from(span.file_name);
-        let path = if path.is_relative() {
-            workspace_root.join(&path)
-        } else {
-            path
-        };
+        let related = diagnostic
+            .spans
+            .iter()
+            .filter(|i| {
+                (&i.file_name, i.line_start, i.column_start)
+                    != (&span.file_name, span.line_start, span.column_start)
+            })
+            .map(|i| RelatedLocation {
+                path: resolve_path(&i.file_name, workspace_root),
+                line: i.line_start,
+                column: i.column_start,
+                label: i.label.clone(),
+            })
+            .collect();
         Self {
-            path,
+            path: resolve_path(&span.file_name, workspace_root),
             line: span.line_start,
             column: span.column_start,
             message: diagnostic.message.clone(),
-            level: diagnostic.level,
+            level: diagnostic.level.into(),
+            label: span.label,
+            related,
+            fixes: fixes::machine_applicable(diagnostic, workspace_root),
+        }
+    }
+}
+
+impl From<DiagnosticLevel> for Level {
+    fn from(level: DiagnosticLevel) -> Self {
+        match level {
+            DiagnosticLevel::Ice => Self::Ice,
+            DiagnosticLevel::Error => Self::Error,
+            DiagnosticLevel::Warning => Self::Warning,
+            DiagnosticLevel::FailureNote => Self::FailureNote,
+            DiagnosticLevel::Help => Self::Help,
+            _ => Self::Note,
         }
     }
 }
+
+impl PartialEq<DiagnosticLevel> for Level {
+    fn eq(&self, other: &DiagnosticLevel) -> bool {
+        *self == Self::from(*other)
+    }
+}
+
+fn resolve_path(file_name: &str, workspace_root: &Path) -> PathBuf {
+    let path = PathBuf::from(file_name);
+    if path.is_relative() {
+        workspace_root.join(&path)
+    } else {
+        path
+    }
+}
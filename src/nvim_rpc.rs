@@ -0,0 +1,270 @@
+use rmpv::Value;
+use serde::Serialize;
+use std::{
+    error::Error,
+    fmt, io,
+    io::{Read, Write},
+    path::Path,
+};
+
+const REQUEST: u64 = 0;
+const RESPONSE: u64 = 1;
+const NOTIFICATION: u64 = 2;
+
+/// Minimal msgpack-RPC client of Neovim's `--listen` socket (or named pipe on Windows).
+pub struct NeovimClient<S> {
+    stream: S,
+    next_id: u32,
+}
+
+#[derive(Debug)]
+pub enum RpcError {
+    Io(io::Error),
+    Encode(String),
+    Decode(String),
+    /// Error reported by Neovim itself, for instance an exception thrown by the called function.
+    Neovim {
+        kind: Option<i64>,
+        message: String,
+    },
+    UnexpectedMessage(Value),
+}
+
+#[cfg(unix)]
+impl NeovimClient<std::os::unix::net::UnixStream> {
+    pub fn connect(address: &Path) -> Result<Self, RpcError> {
+        Ok(Self::new(std::os::unix::net::UnixStream::connect(address)?))
+    }
+}
+
+#[cfg(windows)]
+impl NeovimClient<std::fs::File> {
+    pub fn connect(address: &Path) -> Result<Self, RpcError> {
+        let pipe = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(address)?;
+        Ok(Self::new(pipe))
+    }
+}
+
+impl<S: Read + Write> NeovimClient<S> {
+    pub fn new(stream: S) -> Self {
+        Self { stream, next_id: 0 }
+    }
+
+    /// Calls VimScript function with arguments, which are passed as Vim values (not as strings).
+    pub fn call_function(
+        &mut self,
+        function: &str,
+        args: &[impl Serialize],
+    ) -> Result<Value, RpcError> {
+        let args = args
+            .iter()
+            .map(|arg| serde_json::to_value(arg).map(json_to_value))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| RpcError::Encode(err.to_string()))?;
+        self.request(
+            "nvim_call_function",
+            vec![Value::from(function), Value::Array(args)],
+        )
+    }
+
+    pub fn request(&mut self, method: &str, params: Vec<Value>) -> Result<Value, RpcError> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let request = Value::Array(vec![
+            Value::from(REQUEST),
+            Value::from(id),
+            Value::from(method),
+            Value::Array(params),
+        ]);
+        let mut buffer = Vec::new();
+        rmpv::encode::write_value(&mut buffer, &request)
+            .map_err(|err| RpcError::Encode(err.to_string()))?;
+        self.stream.write_all(&buffer)?;
+        self.stream.flush()?;
+
+        loop {
+            let message = rmpv::decode::read_value(&mut self.stream).map_err(|err| match err {
+                rmpv::decode::Error::InvalidMarkerRead(err)
+                | rmpv::decode::Error::InvalidDataRead(err) => RpcError::Io(err),
+                err => RpcError::Decode(err.to_string()),
+            })?;
+            match message.as_array().map(Vec::as_slice) {
+                Some([kind, _, _]) if kind.as_u64() == Some(NOTIFICATION) => (),
+                Some([kind, response_id, error, result])
+                    if kind.as_u64() == Some(RESPONSE)
+                        && response_id.as_u64() == Some(u64::from(id)) =>
+                {
+                    return if error.is_nil() {
+                        Ok(result.clone())
+                    } else {
+                        Err(RpcError::from_neovim_error(error))
+                    };
+                },
+                _ => return Err(RpcError::UnexpectedMessage(message)),
+            }
+        }
+    }
+}
+
+impl RpcError {
+    /// No Neovim instance listens on the address.
+    pub fn is_not_listening(&self) -> bool {
+        matches!(
+            self,
+            Self::Io(err)
+                if matches!(err.kind(), io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused)
+        )
+    }
+
+    // [type, message], where type is an index of error_types in API metadata
+    fn from_neovim_error(error: &Value) -> Self {
+        match error.as_array().map(Vec::as_slice) {
+            Some([kind, message]) if message.is_str() => Self::Neovim {
+                kind: kind.as_i64(),
+                message: message.as_str().unwrap_or_default().to_owned(),
+            },
+            _ => Self::Neovim {
+                kind: None,
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+// Structs become dictionaries, not arrays as in rmpv's serializer
+fn json_to_value(json: serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Nil,
+        serde_json::Value::Bool(value) => Value::Boolean(value),
+        serde_json::Value::Number(number) => number
+            .as_u64()
+            .map(Value::from)
+            .or_else(|| number.as_i64().map(Value::from))
+            .unwrap_or_else(|| Value::from(number.as_f64().unwrap_or_default())),
+        serde_json::Value::String(value) => Value::from(value),
+        serde_json::Value::Array(values) => {
+            Value::Array(values.into_iter().map(json_to_value).collect())
+        },
+        serde_json::Value::Object(map) => Value::Map(
+            map.into_iter()
+                .map(|(key, value)| (Value::from(key), json_to_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+impl From<io::Error> for RpcError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to communicate with nvim: {err}"),
+            Self::Encode(err) => write!(f, "failed to encode request to nvim: {err}"),
+            Self::Decode(err) => write!(f, "failed to decode response of nvim: {err}"),
+            Self::Neovim { message, .. } => write!(f, "nvim error: {message}"),
+            Self::UnexpectedMessage(message) => {
+                write!(f, "unexpected message from nvim: {message}")
+            },
+        }
+    }
+}
+
+impl Error for RpcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use std::{env, fs, os::unix::net::UnixListener, thread};
+
+    #[test]
+    fn call_function() -> Result<()> {
+        let dir = env::temp_dir().join(format!("cargo-limit-nvim-rpc-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let address = dir.join("socket");
+        let listener = UnixListener::bind(&address)?;
+
+        let server = thread::spawn(move || -> Vec<Value> {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut requests = Vec::new();
+            for error in [
+                Value::Nil,
+                Value::from(vec![Value::from(0), "E605: boom".into()]),
+            ] {
+                let request = rmpv::decode::read_value(&mut stream).unwrap();
+                let id = request[1].clone();
+                requests.push(request);
+
+                let notification = Value::from(vec![
+                    Value::from(NOTIFICATION),
+                    "nvim_buf_lines_event".into(),
+                    Value::Array(Vec::new()),
+                ]);
+                let response = Value::from(vec![Value::from(RESPONSE), id, error, 0.into()]);
+                rmpv::encode::write_value(&mut stream, &notification).unwrap();
+                rmpv::encode::write_value(&mut stream, &response).unwrap();
+            }
+            requests
+        });
+
+        #[derive(Serialize)]
+        struct Data<'a> {
+            text: &'a str,
+            lines: Vec<usize>,
+        }
+        let data = Data {
+            text: "\"quoted\" 'text'\n",
+            lines: vec![1, 2],
+        };
+        let mut client = NeovimClient::connect(&address)?;
+        assert_eq!(client.call_function("g:Open", &[&data])?, Value::from(0));
+        match client.call_function("g:Open", &[&data]) {
+            Err(RpcError::Neovim { kind, message }) => {
+                assert_eq!((kind, message.as_str()), (Some(0), "E605: boom"));
+            },
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        let expected_data = Value::Map(vec![
+            ("lines".into(), Value::from(vec![Value::from(1), 2.into()])),
+            ("text".into(), "\"quoted\" 'text'\n".into()),
+        ]);
+        let requests = server.join().unwrap();
+        assert_eq!(
+            requests,
+            (0..2)
+                .map(|id| Value::from(vec![
+                    Value::from(REQUEST),
+                    id.into(),
+                    "nvim_call_function".into(),
+                    Value::from(vec![
+                        "g:Open".into(),
+                        Value::from(vec![expected_data.clone()])
+                    ]),
+                ]))
+                .collect::<Vec<_>>()
+        );
+
+        let not_listening = NeovimClient::connect(&dir.join("missing")).err();
+        assert!(not_listening.is_some_and(|err| err.is_not_listening()));
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
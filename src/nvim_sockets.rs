@@ -0,0 +1,55 @@
+use crate::env_vars;
+use std::{env, fs::Metadata, os::unix::fs::MetadataExt, path::PathBuf};
+
+const SOCKETS_DIR_NAME: &str = "cargo-limit";
+
+/// Directory with sockets of Neovim instances of current user, the plugin uses the same one:
+/// `$XDG_RUNTIME_DIR/cargo-limit`, otherwise `/tmp/cargo-limit-<uid>`.
+pub fn sockets_dir() -> PathBuf {
+    env::var_os(env_vars::XDG_RUNTIME_DIR)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .map_or_else(
+            || PathBuf::from(format!("/tmp/{SOCKETS_DIR_NAME}-{}", current_uid())),
+            |dir| dir.join(SOCKETS_DIR_NAME),
+        )
+}
+
+/// Other users can neither put sockets into the directory nor connect to them.
+pub fn is_private_dir(metadata: &Metadata) -> bool {
+    metadata.is_dir() && is_owned_by_current_user(metadata) && metadata.mode() & 0o077 == 0
+}
+
+pub fn is_owned_by_current_user(metadata: &Metadata) -> bool {
+    metadata.uid() == current_uid()
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn private_dir() -> Result<()> {
+        let dir = env::temp_dir().join(format!("cargo-limit-sockets-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+        assert!(is_private_dir(&fs::symlink_metadata(&dir)?));
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755))?;
+        assert!(!is_private_dir(&fs::symlink_metadata(&dir)?));
+
+        let file = dir.join("socket");
+        fs::write(&file, "")?;
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o700))?;
+        assert!(!is_private_dir(&fs::symlink_metadata(&file)?));
+        assert!(is_owned_by_current_user(&fs::symlink_metadata(&file)?));
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
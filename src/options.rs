@@ -1,14 +1,81 @@
-use crate::{NVIM, cargo_toml::CargoToml, env_vars, process::CARGO_EXECUTABLE};
+use crate::{
+    NVIM, annotations,
+    baseline::BASELINE_FILE_NAME,
+    cargo_toml::CargoToml,
+    config::Config,
+    env_vars,
+    process::{CARGO_EXECUTABLE, CargoProcess},
+    stderr::Progress,
+};
 use anyhow::{Context, Result, format_err};
+use cargo_metadata::Metadata;
 use const_format::concatcp;
 use itertools::Either;
-use std::{env, io, io::IsTerminal, iter, path::Path, str::FromStr, time::Duration};
+use std::{
+    env, io,
+    io::IsTerminal,
+    iter,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 use which::which;
 
+pub const RUN_SUBCOMMAND: &str = "run";
+pub const TEST_SUBCOMMAND: &str = "test";
+const FIX_SUBCOMMAND: &str = "fix";
+const CHECK_SUBCOMMAND: &str = "check";
+
+// Fixes are reviewed and written by cargo-limit itself
+const IGNORED_FIX_ARGS: &[&str] = &[
+    "--allow-dirty",
+    "--allow-no-vcs",
+    "--allow-staged",
+    "--broken-code",
+];
+const UNSUPPORTED_FIX_ARGS: &[&str] = &["--edition", "--edition-idioms"];
+
+const WORKSPACE_ARGS: &[&str] = &["--workspace", "--all"];
+const PACKAGE_SHORT_ARG: &str = "-p";
+const MANIFEST_PATH_ARG: &str = "--manifest-path";
+const PART_OF_WORKSPACE_ARGS: &[&str] = &[
+    "--package",
+    "--exclude",
+    "--lib",
+    "--bin",
+    "--bins",
+    "--example",
+    "--examples",
+    "--test",
+    "--tests",
+    "--bench",
+    "--benches",
+    "--doc",
+];
+
 const EXECUTABLE_PREFIX: &str = concatcp!(CARGO_EXECUTABLE, "-l");
 
 const APP_ARGS_DELIMITER: &str = "--";
 
+const LIMIT_OPTION_PREFIX: &str = "--limit-";
+const LIMIT_MESSAGES: &str = "messages";
+const LIMIT_TIME: &str = "time";
+const LIMIT_ASC: &str = "asc";
+const LIMIT_FORCE_WARN: &str = "force-warn";
+const LIMIT_DEPS_WARN: &str = "deps-warn";
+const LIMIT_EDITOR: &str = "editor";
+const LIMIT_WATCH: &str = "watch";
+const LIMIT_CHECKSTYLE_REPORT: &str = "checkstyle-report";
+const LIMIT_JUNIT_REPORT: &str = "junit-report";
+const LIMIT_GITLAB_REPORT: &str = "gitlab-report";
+const LIMIT_WARN_BUDGET: &str = "warn-budget";
+const LIMIT_WARN_BUDGET_FILE: &str = "warn-budget-file";
+const LIMIT_WARN_RATCHET: &str = "warn-ratchet";
+const LIMIT_BASELINE: &str = "baseline";
+const LIMIT_TEST_SUMMARY: &str = "test-summary";
+const LIMIT_PROGRESS: &str = "progress";
+const LIMIT_INTERACTIVE: &str = "interactive";
+
 const MESSAGE_FORMAT: &str = "--message-format=";
 const MESSAGE_FORMAT_JSON: &str = concatcp!(MESSAGE_FORMAT, JSON_FORMAT);
 const MESSAGE_FORMAT_JSON_WITH_COLORS: &str = concatcp!(MESSAGE_FORMAT, JSON_FORMAT_WITH_COLORS);
@@ -19,8 +86,10 @@ const JSON_FORMAT_WITH_COLORS: &str = "json-diagnostic-rendered-ansi";
 const JSON_FORMAT_SHORT: &str = "json-diagnostic-short";
 const SHORT_FORMAT: &str = "short";
 const HUMAN_FORMAT: &str = "human";
+const SARIF_FORMAT: &str = "sarif";
 const VALID_MESSAGE_FORMATS: &[&str] = &[
     HUMAN_FORMAT,
+    SARIF_FORMAT,
     SHORT_FORMAT,
     JSON_FORMAT,
     JSON_FORMAT_SHORT,
@@ -39,7 +108,10 @@ pub struct Options {
     cargo_args: Vec<String>,
     args_after_app_args_delimiter: Vec<String>,
     terminal_supports_colors: bool,
+    apps_run_in_pty: bool,
+    reviews_fixes: bool,
 
+    pub github_annotations: bool,
     pub color: String,
     pub limit_messages: usize,
     pub time_limit_after_error: Option<Duration>,
@@ -48,10 +120,27 @@ pub struct Options {
     pub show_dependencies_warnings: bool,
     pub open_in_external_app: String,
     pub open_in_external_app_on_warnings: bool,
+    pub watch: bool,
+    pub checkstyle_report: Option<PathBuf>,
+    pub junit_report: Option<PathBuf>,
+    pub gitlab_report: Option<PathBuf>,
+    pub warn_budget: Option<usize>,
+    pub warn_budget_file: Option<PathBuf>,
+    pub warn_ratchet: bool,
+    pub checks_part_of_workspace: bool,
+    pub baseline_file: Option<PathBuf>,
+    pub test_summary: bool,
+    pub progress: Progress,
+    pub interactive: bool,
+    pub include_paths: Vec<String>,
+    pub exclude_paths: Vec<String>,
+    pub include_lints: Vec<String>,
+    pub exclude_lints: Vec<String>,
     pub help: bool,
     pub version: bool,
     pub keep_going: bool,
     pub json_message_format: bool,
+    pub sarif_message_format: bool,
     short_message_format: bool,
 }
 
@@ -68,6 +157,9 @@ impl Default for Options {
             cargo_args: Vec::new(),
             args_after_app_args_delimiter: Vec::new(),
             terminal_supports_colors: true,
+            apps_run_in_pty: false,
+            reviews_fixes: false,
+            github_annotations: false,
             color: COLOR_AUTO.to_string(),
             limit_messages: 0,
             time_limit_after_error: Some(Duration::from_secs(1)),
@@ -80,10 +172,27 @@ impl Default for Options {
                 .unwrap_or_default()
                 .to_owned(),
             open_in_external_app_on_warnings: false,
+            watch: false,
+            checkstyle_report: None,
+            junit_report: None,
+            gitlab_report: None,
+            warn_budget: None,
+            warn_budget_file: None,
+            warn_ratchet: false,
+            checks_part_of_workspace: false,
+            baseline_file: Some(PathBuf::from(BASELINE_FILE_NAME)),
+            test_summary: false,
+            progress: Progress::Show,
+            interactive: false,
+            include_paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            include_lints: Vec::new(),
+            exclude_lints: Vec::new(),
             help: false,
             version: false,
             keep_going: false,
             json_message_format: false,
+            sarif_message_format: false,
             short_message_format: false,
         }
     }
@@ -103,15 +212,94 @@ impl Options {
             .chain(self.args_after_app_args_delimiter.clone())
     }
 
+    pub fn subcommand(&self) -> &str {
+        self.cargo_args.first().map_or("", String::as_str)
+    }
+
+    pub fn shows_test_summary(&self) -> bool {
+        self.test_summary && self.subcommand() == TEST_SUBCOMMAND && !self.help && !self.version
+    }
+
+    pub fn shows_app_panics(&self) -> bool {
+        self.subcommand() == RUN_SUBCOMMAND
+            && !self.open_in_external_app.is_empty()
+            && !self.help
+            && !self.version
+    }
+
+    /// `cargo lfix` in interactive mode runs `cargo check` instead and applies reviewed fixes.
+    pub fn reviews_fixes(&self) -> bool {
+        self.reviews_fixes
+    }
+
+    /// Whether cargo checks only some packages or targets, so not every warning is counted.
+    pub fn selects_part_of_workspace(&self, metadata: &Metadata, current_dir: &Path) -> bool {
+        let mut all_members = false;
+        let mut manifest_dir = current_dir.to_path_buf();
+        let mut args = self.cargo_args.iter();
+        while let Some(arg) = args.next() {
+            let manifest_path = if arg == MANIFEST_PATH_ARG {
+                args.next().map(String::as_str)
+            } else {
+                arg.strip_prefix(MANIFEST_PATH_ARG)
+                    .and_then(|i| i.strip_prefix('='))
+            };
+            if let Some(manifest_path) = manifest_path {
+                let manifest_path = current_dir.join(manifest_path);
+                manifest_dir = manifest_path.parent().unwrap_or(current_dir).to_path_buf();
+            } else if WORKSPACE_ARGS.contains(&arg.as_str()) {
+                all_members = true;
+            } else if arg.starts_with(PACKAGE_SHORT_ARG)
+                || PART_OF_WORKSPACE_ARGS.iter().any(|i| {
+                    arg.strip_prefix(i)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+                })
+            {
+                return true;
+            }
+        }
+        if all_members {
+            return false;
+        }
+
+        // Without arguments cargo checks the package of the current directory,
+        // or default members when it's the workspace root
+        let members = metadata.workspace_packages();
+        let current_package = members
+            .iter()
+            .filter_map(|package| package.manifest_path.parent())
+            .filter(|package_dir| manifest_dir.starts_with(package_dir))
+            .max_by_key(|package_dir| package_dir.as_str().len());
+        match current_package {
+            Some(package_dir) if package_dir != metadata.workspace_root => members.len() > 1,
+            _ if metadata.workspace_default_members.is_available() => {
+                metadata.workspace_default_members.len() < members.len()
+            },
+            _ => metadata.root_package().is_some() && members.len() > 1,
+        }
+    }
+
     pub fn from_os_env(current_exe: String, workspace_root: Option<&Path>) -> Result<Self> {
-        Self::new()?.process_args(current_exe, env::args(), workspace_root)
+        Self::from_args(current_exe, env::args(), workspace_root)
+    }
+
+    pub fn from_args(
+        current_exe: String,
+        args: impl Iterator<Item = String>,
+        workspace_root: Option<&Path>,
+    ) -> Result<Self> {
+        Self::new(Config::load()?)?.process_args(current_exe, args, workspace_root)
     }
 
-    fn new() -> Result<Self> {
+    fn new(config: Config) -> Result<Self> {
         let mut result = Self {
             terminal_supports_colors: io::stderr().is_terminal(),
+            apps_run_in_pty: CargoProcess::runs_stdout_in_pty(),
+            github_annotations: annotations::github_actions_detected(),
+            gitlab_report: annotations::default_gitlab_report(),
             ..Self::default()
         };
+        result.apply_config(config);
 
         {
             let mut seconds = result
@@ -121,12 +309,7 @@ impl Options {
                 .unwrap_or(0);
             Self::parse_var(env_vars::TIME_LIMIT, &mut seconds)?;
 
-            let duration = Duration::from_secs(seconds);
-            result.time_limit_after_error = if duration > Duration::from_secs(0) {
-                Some(duration)
-            } else {
-                None
-            };
+            result.set_time_limit_after_error(seconds);
         }
 
         Self::parse_var(env_vars::MSG_LIMIT, &mut result.limit_messages)?;
@@ -137,10 +320,87 @@ impl Options {
         )?;
         Self::parse_var(env_vars::DEPS_WARN, &mut result.show_dependencies_warnings)?;
         Self::parse_var(env_vars::EDITOR, &mut result.open_in_external_app)?;
+        Self::parse_var(env_vars::WATCH, &mut result.watch)?;
+        Self::parse_optional_var(env_vars::CHECKSTYLE_REPORT, &mut result.checkstyle_report)?;
+        Self::parse_optional_var(env_vars::JUNIT_REPORT, &mut result.junit_report)?;
+        Self::parse_optional_var(env_vars::GITLAB_REPORT, &mut result.gitlab_report)?;
+        Self::parse_optional_var(env_vars::WARN_BUDGET, &mut result.warn_budget)?;
+        Self::parse_optional_var(env_vars::WARN_BUDGET_FILE, &mut result.warn_budget_file)?;
+        Self::parse_var(env_vars::WARN_RATCHET, &mut result.warn_ratchet)?;
+        Self::parse_optional_var(env_vars::BASELINE, &mut result.baseline_file)?;
+        Self::parse_var(env_vars::TEST_SUMMARY, &mut result.test_summary)?;
+        Self::parse_var(env_vars::PROGRESS, &mut result.progress)?;
+        Self::parse_var(env_vars::INTERACTIVE, &mut result.interactive)?;
 
         Ok(result)
     }
 
+    fn apply_config(&mut self, config: Config) {
+        let Config {
+            msg_limit,
+            time_limit,
+            asc,
+            force_warn,
+            deps_warn,
+            editor,
+            watch,
+            checkstyle_report,
+            junit_report,
+            gitlab_report,
+            warn_budget,
+            warn_budget_file,
+            warn_ratchet,
+            baseline,
+            test_summary,
+            progress,
+            interactive,
+            include_paths,
+            exclude_paths,
+            include_lints,
+            exclude_lints,
+        } = config;
+
+        if let Some(seconds) = time_limit {
+            self.set_time_limit_after_error(seconds);
+        }
+        self.limit_messages = msg_limit.unwrap_or(self.limit_messages);
+        self.ascending_messages_order = asc.unwrap_or(self.ascending_messages_order);
+        self.show_warnings_if_errors_exist =
+            force_warn.unwrap_or(self.show_warnings_if_errors_exist);
+        self.show_dependencies_warnings = deps_warn.unwrap_or(self.show_dependencies_warnings);
+        if let Some(editor) = editor {
+            self.open_in_external_app = editor;
+        }
+        self.watch = watch.unwrap_or(self.watch);
+        self.checkstyle_report = checkstyle_report.or(self.checkstyle_report.take());
+        self.junit_report = junit_report.or(self.junit_report.take());
+        if let Some(gitlab_report) = gitlab_report {
+            self.gitlab_report = Some(gitlab_report).filter(|path| !path.as_os_str().is_empty());
+        }
+        self.warn_budget = warn_budget.or(self.warn_budget);
+        self.warn_budget_file = warn_budget_file.or(self.warn_budget_file.take());
+        self.warn_ratchet = warn_ratchet.unwrap_or(self.warn_ratchet);
+        if let Some(baseline) = baseline {
+            self.baseline_file = Some(baseline).filter(|path| !path.as_os_str().is_empty());
+        }
+        self.test_summary = test_summary.unwrap_or(self.test_summary);
+        self.progress = progress.unwrap_or(self.progress);
+        self.interactive = interactive.unwrap_or(self.interactive);
+        self.include_paths = include_paths.unwrap_or_default();
+        self.exclude_paths = exclude_paths.unwrap_or_default();
+        self.include_lints = include_lints.unwrap_or_default();
+        self.exclude_lints = exclude_lints.unwrap_or_default();
+    }
+
+    fn set_time_limit_after_error(&mut self, seconds: u64) {
+        let duration = Duration::from_secs(seconds);
+        self.time_limit_after_error = if duration > Duration::from_secs(0) {
+            Some(duration)
+        } else {
+            None
+        };
+    }
+
     fn process_args(
         mut self,
         current_exe: String,
@@ -165,6 +425,9 @@ impl Options {
             &mut args_before_app_args_delimiter,
             &mut app_args_started,
         )?;
+        if self.interactive && subcommand == FIX_SUBCOMMAND && !self.help && !self.version {
+            self.review_fixes(&mut args_before_app_args_delimiter)?;
+        }
         self.cargo_args.push(self.message_format().to_owned());
         self.cargo_args.extend(args_before_app_args_delimiter);
 
@@ -217,19 +480,11 @@ impl Options {
                 let format = passed_args.next().context(
                     "the argument '--message-format <FMT>' requires a value but none was supplied",
                 )?;
-                Self::validate_message_format(&format)?;
-                if format.starts_with(JSON_FORMAT) {
-                    self.json_message_format = true;
-                } else if format == SHORT_FORMAT {
-                    self.short_message_format = true;
-                }
+                self.set_message_format(&format)?;
             } else if let Some(format) = arg.strip_prefix(MESSAGE_FORMAT) {
-                Self::validate_message_format(format)?;
-                if format.starts_with(JSON_FORMAT) {
-                    self.json_message_format = true;
-                } else if format == SHORT_FORMAT {
-                    self.short_message_format = true;
-                }
+                self.set_message_format(format)?;
+            } else if let Some(option) = arg.strip_prefix(LIMIT_OPTION_PREFIX) {
+                self.parse_limit_option(option, passed_args)?;
             } else if arg == APP_ARGS_DELIMITER {
                 *app_args_started = true;
                 break;
@@ -241,10 +496,155 @@ impl Options {
         Ok(())
     }
 
+    fn parse_limit_option(
+        &mut self,
+        option: &str,
+        passed_args: &mut impl Iterator<Item = String>,
+    ) -> Result<()> {
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_owned())),
+            None => (option, None),
+        };
+
+        match name {
+            LIMIT_MESSAGES => {
+                self.limit_messages = Self::parse_limit_option_value(name, value, passed_args)?;
+            },
+            LIMIT_TIME => {
+                let seconds = Self::parse_limit_option_value(name, value, passed_args)?;
+                self.set_time_limit_after_error(seconds);
+            },
+            LIMIT_ASC => {
+                self.ascending_messages_order = Self::parse_limit_flag(name, value)?;
+            },
+            LIMIT_FORCE_WARN => {
+                self.show_warnings_if_errors_exist = Self::parse_limit_flag(name, value)?;
+            },
+            LIMIT_DEPS_WARN => {
+                self.show_dependencies_warnings = Self::parse_limit_flag(name, value)?;
+            },
+            LIMIT_EDITOR => {
+                self.open_in_external_app =
+                    Self::parse_limit_option_value(name, value, passed_args)?;
+            },
+            LIMIT_WATCH => {
+                self.watch = Self::parse_limit_flag(name, value)?;
+            },
+            LIMIT_CHECKSTYLE_REPORT => {
+                self.checkstyle_report =
+                    Self::parse_limit_optional_value(name, value, passed_args)?;
+            },
+            LIMIT_JUNIT_REPORT => {
+                self.junit_report = Self::parse_limit_optional_value(name, value, passed_args)?;
+            },
+            LIMIT_GITLAB_REPORT => {
+                self.gitlab_report = Self::parse_limit_optional_value(name, value, passed_args)?;
+            },
+            LIMIT_WARN_BUDGET => {
+                self.warn_budget = Self::parse_limit_optional_value(name, value, passed_args)?;
+            },
+            LIMIT_WARN_BUDGET_FILE => {
+                self.warn_budget_file = Self::parse_limit_optional_value(name, value, passed_args)?;
+            },
+            LIMIT_WARN_RATCHET => {
+                self.warn_ratchet = Self::parse_limit_flag(name, value)?;
+            },
+            LIMIT_BASELINE => {
+                self.baseline_file = Self::parse_limit_optional_value(name, value, passed_args)?;
+            },
+            LIMIT_TEST_SUMMARY => {
+                self.test_summary = Self::parse_limit_flag(name, value)?;
+            },
+            LIMIT_PROGRESS => {
+                self.progress = Self::parse_limit_option_value(name, value, passed_args)?;
+            },
+            LIMIT_INTERACTIVE => {
+                self.interactive = Self::parse_limit_flag(name, value)?;
+            },
+            _ => {
+                return Err(format_err!(
+                    "unexpected argument '{LIMIT_OPTION_PREFIX}{name}' found"
+                ));
+            },
+        }
+
+        Ok(())
+    }
+
+    fn parse_limit_option_value<T: FromStr>(
+        name: &str,
+        value: Option<String>,
+        passed_args: &mut impl Iterator<Item = String>,
+    ) -> Result<T>
+    where
+        <T as FromStr>::Err: std::error::Error + Sync + Send + 'static,
+    {
+        let value = value.or_else(|| passed_args.next()).with_context(|| {
+            format!(
+                "the argument '{LIMIT_OPTION_PREFIX}{name}' requires a value but none was supplied"
+            )
+        })?;
+        value
+            .parse()
+            .with_context(|| format!("invalid value '{value}' for '{LIMIT_OPTION_PREFIX}{name}'"))
+    }
+
+    fn parse_limit_optional_value<T: FromStr>(
+        name: &str,
+        value: Option<String>,
+        passed_args: &mut impl Iterator<Item = String>,
+    ) -> Result<Option<T>>
+    where
+        <T as FromStr>::Err: std::error::Error + Sync + Send + 'static,
+    {
+        let value: String = Self::parse_limit_option_value(name, value, passed_args)?;
+        Self::parse_optional(&value)
+            .with_context(|| format!("invalid value '{value}' for '{LIMIT_OPTION_PREFIX}{name}'"))
+    }
+
+    fn parse_limit_flag(name: &str, value: Option<String>) -> Result<bool> {
+        match value {
+            Some(value) => value.parse().with_context(|| {
+                format!("invalid value '{value}' for '{LIMIT_OPTION_PREFIX}{name}'")
+            }),
+            None => Ok(true),
+        }
+    }
+
+    fn review_fixes(&mut self, args: &mut Vec<String>) -> Result<()> {
+        if let Some(arg) = args
+            .iter()
+            .find(|arg| UNSUPPORTED_FIX_ARGS.contains(&arg.as_str()))
+        {
+            return Err(format_err!(
+                "the argument '{arg}' is not supported by \
+                 '{LIMIT_OPTION_PREFIX}{LIMIT_INTERACTIVE}'"
+            ));
+        }
+        args.retain(|arg| !IGNORED_FIX_ARGS.contains(&arg.as_str()));
+        self.cargo_args[0] = CHECK_SUBCOMMAND.to_owned();
+        self.reviews_fixes = true;
+        // All suggestions are needed, not only the ones that came before the first error
+        self.time_limit_after_error = None;
+        Ok(())
+    }
+
+    fn set_message_format(&mut self, format: &str) -> Result<()> {
+        Self::validate_message_format(format)?;
+        if format.starts_with(JSON_FORMAT) {
+            self.json_message_format = true;
+        } else if format == SHORT_FORMAT {
+            self.short_message_format = true;
+        } else if format == SARIF_FORMAT {
+            self.sarif_message_format = true;
+        }
+        Ok(())
+    }
+
     fn message_format(&self) -> &str {
         if self.short_message_format {
             MESSAGE_FORMAT_JSON_SHORT
-        } else if self.json_message_format {
+        } else if self.json_message_format || self.sarif_message_format {
             MESSAGE_FORMAT_JSON
         } else if self.color == COLOR_AUTO {
             if self.terminal_supports_colors {
@@ -267,7 +667,12 @@ impl Options {
         app_color_is_set: bool,
         workspace_root: Option<&Path>,
     ) -> Result<()> {
-        let is_test = subcommand == "test";
+        if self.apps_run_in_pty {
+            // They detect colors support on their own
+            return Ok(());
+        }
+
+        let is_test = subcommand == TEST_SUBCOMMAND;
         let is_bench = subcommand == "bench";
         let command_supports_color_arg = is_test || is_bench;
         if command_supports_color_arg
@@ -284,8 +689,8 @@ impl Options {
                 unreachable!()
             };
             if all_items_have_harness {
-                // Workaround for apps that can't understand that terminal supports colors.
-                // To fix that properly we need to run apps in pty.
+                // Workaround for apps that can't understand that terminal supports colors,
+                // when they aren't run in pty (on Windows or when stdout is redirected).
                 // https://github.com/cargo-limit/cargo-limit/issues/4#issuecomment-833692334
                 self.add_color_arg(COLOR_ALWAYS);
             }
@@ -318,6 +723,25 @@ impl Options {
         Ok(())
     }
 
+    fn parse_optional_var<T: FromStr>(key: &str, value: &mut Option<T>) -> Result<()>
+    where
+        <T as FromStr>::Err: std::error::Error + Sync + Send + 'static,
+    {
+        if let Ok(new_value) = env::var(key) {
+            *value =
+                Self::parse_optional(&new_value).with_context(|| format!("invalid {key} value"))?;
+        }
+        Ok(())
+    }
+
+    fn parse_optional<T: FromStr>(value: &str) -> Result<Option<T>, <T as FromStr>::Err> {
+        if value.is_empty() {
+            Ok(None)
+        } else {
+            value.parse().map(Some)
+        }
+    }
+
     fn validate_color(&self) -> Result<()> {
         if !VALID_COLORS.contains(&self.color.as_str()) {
             return Err(format_err!(
@@ -392,6 +816,7 @@ impl ParsedSubcommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cargo_metadata::MetadataCommand;
     use pretty_assertions::assert_eq;
 
     const STUB_MINIMAL: &str = "minimal";
@@ -642,6 +1067,254 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn limit_options() -> Result<()> {
+        assert_options(
+            vec![
+                "cargo-lbuild",
+                "--limit-messages",
+                "3",
+                "-v",
+                "--limit-force-warn",
+                "--limit-time=0",
+                "--limit-watch",
+                "--limit-checkstyle-report",
+                "target/checkstyle.xml",
+                "--limit-gitlab-report=gl-code-quality-report.json",
+            ],
+            vec![
+                "build",
+                "--message-format=json-diagnostic-rendered-ansi",
+                "-v",
+            ],
+            vec![],
+            Options {
+                limit_messages: 3,
+                show_warnings_if_errors_exist: true,
+                time_limit_after_error: None,
+                watch: true,
+                checkstyle_report: Some(PathBuf::from("target/checkstyle.xml")),
+                gitlab_report: Some(PathBuf::from("gl-code-quality-report.json")),
+                ..Options::default()
+            },
+            STUB_MINIMAL,
+        )?;
+
+        assert_options(
+            vec![
+                "cargo-lbuild",
+                "--limit-asc=false",
+                "--limit-deps-warn=true",
+                "--limit-editor",
+                "",
+                "--limit-junit-report=",
+                "--limit-warn-budget=10",
+                "--limit-warn-budget-file",
+                ".cargo-limit-warnings",
+                "--limit-warn-ratchet",
+                "--limit-baseline=",
+                "--limit-test-summary=true",
+                "--limit-progress",
+                "collapse",
+                "--limit-interactive=false",
+            ],
+            vec!["build", "--message-format=json-diagnostic-rendered-ansi"],
+            vec![],
+            Options {
+                ascending_messages_order: false,
+                show_dependencies_warnings: true,
+                open_in_external_app: "".to_owned(),
+                warn_budget: Some(10),
+                warn_budget_file: Some(PathBuf::from(".cargo-limit-warnings")),
+                warn_ratchet: true,
+                baseline_file: None,
+                test_summary: true,
+                progress: Progress::Collapse,
+                ..Options::default()
+            },
+            STUB_MINIMAL,
+        )?;
+
+        assert_cargo_args(
+            vec!["cargo-lrun", "--", "--limit-asc"],
+            vec!["run", "--message-format=json-diagnostic-rendered-ansi"],
+            vec!["--limit-asc"],
+            STUB_MINIMAL,
+        )?;
+
+        for input in [
+            vec!["cargo-lbuild", "--limit-unknown"],
+            vec!["cargo-lbuild", "--limit-messages"],
+            vec!["cargo-lbuild", "--limit-messages=many"],
+            vec!["cargo-lbuild", "--limit-asc=yes"],
+            vec!["cargo-lbuild", "--limit-warn-budget=-1"],
+            vec!["cargo-lbuild", "--limit-progress=quiet"],
+        ] {
+            assert!(
+                Options::default()
+                    .process_args(input[0].to_owned(), to_string(input), None)
+                    .is_err()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn interactive_fix() -> Result<()> {
+        let reviews_fixes = || Options {
+            reviews_fixes: true,
+            interactive: true,
+            time_limit_after_error: None,
+            ..Options::default()
+        };
+        assert_options(
+            vec!["cargo-lfix", "--limit-interactive"],
+            vec!["check", "--message-format=json-diagnostic-rendered-ansi"],
+            vec![],
+            reviews_fixes(),
+            STUB_MINIMAL,
+        )?;
+        assert_options(
+            vec![
+                "cargo-lfix",
+                "--allow-dirty",
+                "--limit-interactive",
+                "--lib",
+                "--broken-code",
+                "--allow-staged",
+                "--allow-no-vcs",
+                "--",
+                "--allow-dirty",
+            ],
+            vec![
+                "check",
+                "--message-format=json-diagnostic-rendered-ansi",
+                "--lib",
+            ],
+            vec!["--allow-dirty"],
+            reviews_fixes(),
+            STUB_MINIMAL,
+        )?;
+        assert_cargo_args(
+            vec!["cargo-lfix", "--allow-dirty", "--broken-code"],
+            vec![
+                "fix",
+                "--message-format=json-diagnostic-rendered-ansi",
+                "--allow-dirty",
+                "--broken-code",
+            ],
+            vec![],
+            STUB_MINIMAL,
+        )?;
+
+        for arg in ["--edition", "--edition-idioms"] {
+            let input = vec!["cargo-lfix", "--limit-interactive", arg];
+            let err = Options::default()
+                .process_args(input[0].to_owned(), to_string(input), None)
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("the argument '{arg}' is not supported by '--limit-interactive'")
+            );
+        }
+        assert_cargo_args(
+            vec!["cargo-lfix", "--edition"],
+            vec![
+                "fix",
+                "--message-format=json-diagnostic-rendered-ansi",
+                "--edition",
+            ],
+            vec![],
+            STUB_MINIMAL,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn part_of_workspace() -> Result<()> {
+        let stub = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/stubs/workspace");
+        let member = stub.join("member");
+        let metadata = MetadataCommand::new().current_dir(&stub).no_deps().exec()?;
+        for (input, current_dir, expected) in [
+            (vec!["cargo-lbuild"], &stub, true),
+            (vec!["cargo-lbuild"], &member, true),
+            (vec!["cargo-lbuild", "--workspace"], &member, false),
+            (
+                vec!["cargo-lclippy", "--all", "--all-targets"],
+                &stub,
+                false,
+            ),
+            (vec!["cargo-lbuild", "--workspace", "--lib"], &stub, true),
+            (vec!["cargo-lbuild", "--workspace", "--bin=a"], &stub, true),
+            (vec!["cargo-lbuild", "--workspace", "-pmember"], &stub, true),
+            (
+                vec!["cargo-lbuild", "--workspace", "--exclude", "a"],
+                &stub,
+                true,
+            ),
+            (
+                vec!["cargo-lbuild", "--manifest-path", "member/Cargo.toml"],
+                &stub,
+                true,
+            ),
+            (
+                vec!["cargo-lbuild", "--manifest-path=../Cargo.toml", "--all"],
+                &member,
+                false,
+            ),
+        ] {
+            let options = Options::default().process_args(
+                input[0].to_owned(),
+                to_string(input.clone()),
+                None,
+            )?;
+            assert_eq!(
+                options.selects_part_of_workspace(&metadata, current_dir),
+                expected,
+                "{input:?} in {}",
+                current_dir.display()
+            );
+        }
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let metadata = MetadataCommand::new().current_dir(root).no_deps().exec()?;
+        let options = Options::default().process_args(
+            "cargo-lbuild".to_owned(),
+            to_string(["cargo-lbuild"]),
+            None,
+        )?;
+        assert!(!options.selects_part_of_workspace(&metadata, &root.join("src")));
+        Ok(())
+    }
+
+    #[test]
+    fn gitlab_report_overrides_detected_one() -> Result<()> {
+        let detected = || Options {
+            gitlab_report: Some(PathBuf::from("gl-code-quality-report.json")),
+            ..Options::default()
+        };
+
+        let mut options = detected();
+        options.apply_config(Config::default());
+        assert_eq!(options.gitlab_report, detected().gitlab_report);
+        for (path, expected) in [("", None), ("quality.json", Some("quality.json"))] {
+            let mut options = detected();
+            options.apply_config(Config {
+                gitlab_report: Some(PathBuf::from(path)),
+                ..Config::default()
+            });
+            assert_eq!(options.gitlab_report, expected.map(PathBuf::from));
+
+            let input = vec!["cargo-lbuild", "--limit-gitlab-report", path];
+            let options =
+                detected().process_args(input[0].to_owned(), to_string(input.clone()), None)?;
+            assert_eq!(options.gitlab_report, expected.map(PathBuf::from));
+        }
+        Ok(())
+    }
+
     #[test]
     fn weird_args() -> Result<()> {
         assert_cargo_args(
@@ -697,6 +1370,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sarif_message_format() -> Result<()> {
+        assert_options(
+            vec!["cargo-lclippy", "--message-format", "sarif", "-v"],
+            vec!["clippy", "--message-format=json", "-v"],
+            vec![],
+            Options {
+                sarif_message_format: true,
+                ..Options::default()
+            },
+            STUB_MINIMAL,
+        )?;
+        Ok(())
+    }
+
     #[test]
     fn run_with_color_args() -> Result<()> {
         assert_options(
@@ -742,6 +1430,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn colored_testing_in_pty() -> Result<()> {
+        let input = vec!["cargo-ltest", "--", "--help"];
+        let options = Options {
+            apps_run_in_pty: true,
+            ..Options::default()
+        }
+        .process_args(
+            input[0].to_owned(),
+            to_string(input),
+            Some(&Path::new("tests/stubs").join(STUB_MINIMAL)),
+        )?;
+        assert_eq!(options.args_after_app_args_delimiter, ["--help"]);
+        Ok(())
+    }
+
     #[test]
     fn colored_testing_and_compiling_2() -> Result<()> {
         assert_options(
@@ -0,0 +1,270 @@
+use crate::{
+    io::strip_ansi_escape_codes,
+    libtest::TEST_OUTPUT_HEADER_PREFIX,
+    models::{Level, Location},
+};
+use std::path::{Path, PathBuf};
+
+const THREAD_PREFIX: &str = "thread '";
+const PANICKED_AT: &str = "panicked at ";
+const NOTE_PREFIX: &str = "note: ";
+const BACKTRACE_HEADER: &str = "stack backtrace:";
+const FRAME_LOCATION_PREFIX: &str = "at ";
+
+#[derive(Debug, PartialEq)]
+pub struct Panic {
+    pub thread: Option<String>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Frame {
+    function: String,
+    location: Option<(PathBuf, usize, usize)>,
+}
+
+#[derive(Default)]
+pub struct PanicParser {
+    current: Option<Panic>,
+    state: State,
+    panics: Vec<Panic>,
+}
+
+#[derive(Default, PartialEq)]
+enum State {
+    #[default]
+    Message,
+    AfterMessage,
+    Backtrace,
+}
+
+impl PanicParser {
+    pub fn push_line(&mut self, line: &str) {
+        let line = strip_ansi_escape_codes(line.trim_end_matches(['\r', '\n']));
+        if let Some(panic) = Panic::parse_header(&line) {
+            self.flush();
+            // Rust < 1.73 format has message in the header: panicked at 'message', src/main.rs:1:2
+            self.state = if panic.message.is_empty() {
+                State::Message
+            } else {
+                State::AfterMessage
+            };
+            self.current = Some(panic);
+            return;
+        }
+
+        let Some(panic) = &mut self.current else {
+            return;
+        };
+        if line == BACKTRACE_HEADER {
+            self.state = State::Backtrace;
+        } else if line.starts_with(TEST_OUTPUT_HEADER_PREFIX) {
+            self.flush();
+        } else {
+            match self.state {
+                State::Message if line.is_empty() || line.starts_with(NOTE_PREFIX) => {
+                    self.state = State::AfterMessage;
+                },
+                State::Message => {
+                    if !panic.message.is_empty() {
+                        panic.message.push('\n');
+                    }
+                    panic.message.push_str(&line);
+                },
+                State::AfterMessage => self.flush(),
+                State::Backtrace => {
+                    if !panic.push_frame_line(&line) {
+                        self.flush();
+                    }
+                },
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> Vec<Panic> {
+        self.flush();
+        self.panics
+    }
+
+    fn flush(&mut self) {
+        self.panics.extend(self.current.take());
+    }
+}
+
+impl Panic {
+    pub fn resolve_path(&self, workspace_root: &Path) -> Option<PathBuf> {
+        resolve_workspace_path(&self.path, workspace_root, workspace_root)
+    }
+
+    /// Panic location, followed by backtrace frames that point into the workspace.
+    pub fn into_locations(self, workspace_root: &Path, current_dir: &Path) -> Vec<Location> {
+        let mut result = Vec::new();
+        if let Some(path) = self.resolve_path(workspace_root) {
+            result.push(Location {
+                path,
+                line: self.line,
+                column: self.column,
+                message: self.message,
+                level: Level::Error,
+                label: None,
+                related: Vec::new(),
+                fixes: Vec::new(),
+            });
+        }
+
+        // Unlike file!() paths, backtrace ones are relative to the app's current directory
+        for frame in self.frames {
+            let Some((path, line, column)) = frame.location else {
+                continue;
+            };
+            let Some(path) = resolve_workspace_path(&path, current_dir, workspace_root) else {
+                continue;
+            };
+            if !result.iter().any(|i| i.path == path && i.line == line) {
+                result.push(Location {
+                    path,
+                    line,
+                    column,
+                    message: frame.function,
+                    level: Level::Note,
+                    label: None,
+                    related: Vec::new(),
+                    fixes: Vec::new(),
+                });
+            }
+        }
+        result
+    }
+
+    fn parse_header(line: &str) -> Option<Self> {
+        let (before, after) = line.split_once(PANICKED_AT)?;
+        let thread = before
+            .trim_start()
+            .strip_prefix(THREAD_PREFIX)
+            .and_then(|thread| thread.split_once('\''))
+            .map(|(thread, _)| thread.to_owned());
+
+        let (message, location) = match after.strip_prefix('\'') {
+            Some(after) => {
+                let (message, location) = after.rsplit_once("', ")?;
+                (message.to_owned(), location)
+            },
+            None => (String::new(), after.strip_suffix(':').unwrap_or(after)),
+        };
+        let (path, line, column) = parse_location(location)?;
+
+        Some(Self {
+            thread,
+            path,
+            line,
+            column,
+            message,
+            frames: Vec::new(),
+        })
+    }
+
+    // "   4: app::main"
+    // "             at ./src/main.rs:2:5"
+    fn push_frame_line(&mut self, line: &str) -> bool {
+        let line = line.trim_start();
+        if let Some(location) = line.strip_prefix(FRAME_LOCATION_PREFIX) {
+            if let Some(frame) = self.frames.last_mut() {
+                frame.location = parse_location(location);
+            }
+            true
+        } else if let Some((index, function)) = line.split_once(": ")
+            && index.parse::<usize>().is_ok()
+        {
+            self.frames.push(Frame {
+                function: function.to_owned(),
+                location: None,
+            });
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub fn parse_location(location: &str) -> Option<(PathBuf, usize, usize)> {
+    let location = location.trim_end();
+    let (rest, last) = location.rsplit_once(':')?;
+    let last = last.parse().ok()?;
+    let line_and_column = rest
+        .rsplit_once(':')
+        .and_then(|(path, line)| Some((path, line.parse().ok()?)));
+    Some(match line_and_column {
+        Some((path, line)) => (PathBuf::from(path), line, last),
+        None => (PathBuf::from(rest), last, 1),
+    })
+}
+
+fn resolve_workspace_path(path: &Path, base: &Path, workspace_root: &Path) -> Option<PathBuf> {
+    let path = base.join(path).components().collect::<PathBuf>();
+    (path.starts_with(workspace_root) && path.is_file()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn panic_with_backtrace() {
+        let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let current_dir = workspace_root.join("src");
+        let stderr = "app output
+thread 'main' (24143) panicked at src/io.rs:2:5:
+index out of bounds: the len is 2 but the index is 3
+stack backtrace:
+   0: __rustc::rust_begin_unwind
+             at /rustc/library/std/src/panicking.rs:689:5
+   3: app::inner
+             at ./io.rs:2:5
+   4: app::main
+             at ./lib.rs:8:5
+   5: std::rt::lang_start
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+app output
+";
+
+        let mut panic_parser = PanicParser::default();
+        for line in stderr.lines() {
+            panic_parser.push_line(line);
+        }
+        let locations = panic_parser
+            .finish()
+            .into_iter()
+            .flat_map(|panic| panic.into_locations(workspace_root, &current_dir))
+            .map(|location| {
+                (
+                    location
+                        .path
+                        .strip_prefix(workspace_root)
+                        .unwrap()
+                        .to_owned(),
+                    location.line,
+                    location.message,
+                    location.level,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations,
+            vec![
+                (
+                    "src/io.rs".into(),
+                    2,
+                    "index out of bounds: the len is 2 but the index is 3".to_owned(),
+                    Level::Error
+                ),
+                ("src/lib.rs".into(), 8, "app::main".to_owned(), Level::Note),
+            ]
+        );
+    }
+}
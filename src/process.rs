@@ -1,16 +1,25 @@
+#[cfg(unix)]
+use crate::pty::Pty;
 use crate::{
     env_vars,
-    io::Buffers,
+    io::{Buffers, ChildOutputReader, ChildOutputWriter, FlushingWriter, StatusLine},
     options::{COLOR_ALWAYS, COLOR_NEVER, Options},
+    stderr::{Progress, StderrOutput, StderrParser},
 };
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, format_err};
 use atomig::{Atom, Atomic};
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
 use std::{
     env, fmt,
+    io::{self, IsTerminal},
     path::PathBuf,
-    process::{Child, Command, Stdio},
-    sync::{Arc, atomic::Ordering},
-    thread,
+    process::{Child, Command},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
@@ -19,12 +28,27 @@ pub const CARGO_EXECUTABLE: &str = "cargo";
 #[doc(hidden)]
 pub const NO_EXIT_CODE: i32 = 127;
 
-#[derive(Debug)]
+static RUNNING_PROCESS: Mutex<Option<(u32, Arc<Atomic<State>>)>> = Mutex::new(None);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
 pub struct CargoProcess {
     child: Child,
+    buffers: Option<Buffers>,
+    stderr: Option<JoinHandle<io::Result<StderrOutput>>>,
     state: Arc<Atomic<State>>,
 }
 
+/// Captured stderr is copied on its own thread, while stdout is copied on the caller's one.
+/// Every line is forwarded as soon as it ends (or as soon as it can't be a status line),
+/// so lines of both streams keep the order they were written in, unless the child writes
+/// into both at the same moment; lines themselves are never mixed up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StderrMode {
+    Inherit,
+    MergeWithStdout,
+    Capture,
+}
+
 #[derive(Atom, Debug, Clone, Copy, PartialEq)]
 #[repr(u8)]
 pub enum State {
@@ -66,27 +90,74 @@ impl CargoProcess {
                     .unwrap_or(COLOR_ALWAYS.to_string()),
             )]
         };
-        let child = Command::new(cargo_path)
-            .envs(envs)
-            .args(options.all_args())
-            .stdout(Stdio::piped())
-            .spawn()
-            .context(error_text)?;
+        let mut command = Command::new(cargo_path);
+        command.envs(envs).args(options.all_args());
+        if options.progress != Progress::Show && env::var_os(env_vars::TERM_PROGRESS_WHEN).is_none()
+        {
+            // Progress bar is redrawn without new lines, so it can't be filtered
+            command.env(env_vars::TERM_PROGRESS_WHEN, "never");
+        }
+
+        let stderr_mode = if options.help || options.version {
+            StderrMode::Inherit
+        } else if options.shows_test_summary() {
+            StderrMode::MergeWithStdout
+        } else {
+            StderrMode::Capture
+        };
+        let (child, stdout, stderr) = Self::spawn(command, stderr_mode).context(error_text)?;
+
+        let status_line = StatusLine::default();
+        let stderr = stderr.map(|stderr| {
+            let stderr_parser = StderrParser::new(options, status_line.clone());
+            thread::spawn(move || {
+                stderr_parser.copy(stderr, &mut FlushingWriter::new(io::stderr()))
+            })
+        });
+        let merged_stderr_parser = (stderr_mode == StderrMode::MergeWithStdout)
+            .then(|| StderrParser::new(options, status_line.clone()));
+        let buffers = Buffers::new(stdout, merged_stderr_parser, status_line);
 
         let state = Arc::new(Atomic::new(State::Running));
-        ctrlc::set_handler({
-            let pid = child.id();
-            let state = state.clone();
-            move || {
-                Self::kill(pid, state.clone());
-            }
-        })?;
+        *RUNNING_PROCESS.lock().expect("poisoned lock") = Some((child.id(), state.clone()));
+        Self::set_interruption_handler()?;
 
-        Ok(Self { child, state })
+        Ok(Self {
+            child,
+            buffers: Some(buffers),
+            stderr,
+            state,
+        })
+    }
+
+    pub fn kill_running() {
+        if let Some((pid, state)) = RUNNING_PROCESS.lock().expect("poisoned lock").clone() {
+            Self::kill(pid, state);
+        }
+    }
+
+    /// Apps inherit cargo's stdout, so they detect terminal on their own only when it's pty;
+    /// see `open_output`.
+    pub fn runs_stdout_in_pty() -> bool {
+        cfg!(unix) && io::stdout().is_terminal()
+    }
+
+    pub fn interrupted() -> bool {
+        INTERRUPTED.load(Ordering::Acquire)
     }
 
     pub fn buffers(&mut self) -> Result<Buffers> {
-        Buffers::new(&mut self.child)
+        self.buffers.take().context("cannot read stdout")
+    }
+
+    /// Waits until captured stderr is closed, which happens after the process exits.
+    pub fn stderr_output(&mut self) -> Result<StderrOutput> {
+        match self.stderr.take() {
+            Some(stderr) => Ok(stderr
+                .join()
+                .map_err(|_| format_err!("failed to read stderr"))??),
+            None => Ok(StderrOutput::default()),
+        }
     }
 
     pub fn wait(&mut self) -> Result<i32> {
@@ -119,6 +190,69 @@ impl CargoProcess {
         }
     }
 
+    fn spawn(
+        mut command: Command,
+        stderr_mode: StderrMode,
+    ) -> Result<(Child, ChildOutputReader, Option<ChildOutputReader>)> {
+        let stderr = if stderr_mode == StderrMode::Capture {
+            let (reader, writer) = Self::open_output(io::stderr())?;
+            command.stderr(writer);
+            Some(reader)
+        } else {
+            None
+        };
+
+        let (stdout, writer) = Self::open_output(io::stdout())?;
+        if stderr_mode == StderrMode::MergeWithStdout {
+            command.stderr(writer.try_clone()?);
+        }
+        let child = command.stdout(writer).spawn()?;
+        Ok((child, stdout, stderr))
+    }
+
+    // Running under pseudo-terminal makes apps and test harnesses detect colors support
+    // and terminal width the same way as they do without cargo-limit
+    #[cfg(unix)]
+    fn open_output(
+        terminal: impl IsTerminal + AsRawFd,
+    ) -> Result<(ChildOutputReader, ChildOutputWriter)> {
+        if terminal.is_terminal() {
+            let Pty { master, slave } = Pty::open(&terminal)?;
+            Ok((
+                ChildOutputReader::Pty(master),
+                ChildOutputWriter::Pty(slave),
+            ))
+        } else {
+            Self::open_pipe()
+        }
+    }
+
+    // ConPTY isn't used: it can't be attached with `Command`, merges stdout with stderr
+    // and re-renders output for console width, which would break cargo's JSON messages
+    #[cfg(not(unix))]
+    fn open_output(_terminal: impl IsTerminal) -> Result<(ChildOutputReader, ChildOutputWriter)> {
+        Self::open_pipe()
+    }
+
+    fn open_pipe() -> Result<(ChildOutputReader, ChildOutputWriter)> {
+        let (reader, writer) = io::pipe()?;
+        Ok((
+            ChildOutputReader::Pipe(reader),
+            ChildOutputWriter::Pipe(writer),
+        ))
+    }
+
+    fn set_interruption_handler() -> Result<()> {
+        static HANDLER_IS_SET: AtomicBool = AtomicBool::new(false);
+        if !HANDLER_IS_SET.swap(true, Ordering::AcqRel) {
+            ctrlc::set_handler(|| {
+                INTERRUPTED.store(true, Ordering::Release);
+                Self::kill_running();
+            })?;
+        }
+        Ok(())
+    }
+
     fn kill(pid: u32, state: Arc<Atomic<State>>) {
         if state.try_set_killing() {
             let success = {
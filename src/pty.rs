@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::{
+    fs::File,
+    io::{self, Read},
+    mem::MaybeUninit,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    ptr,
+};
+
+#[derive(Debug)]
+pub struct Pty {
+    pub master: PtyReader,
+    pub slave: OwnedFd,
+}
+
+#[derive(Debug)]
+pub struct PtyReader {
+    master: File,
+}
+
+impl Pty {
+    /// Opens pseudo-terminal with the same window size as `terminal` has.
+    pub fn open(terminal: &impl AsRawFd) -> Result<Self> {
+        let winsize = window_size(terminal);
+
+        let mut master = -1;
+        let mut slave = -1;
+        let result = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                ptr::null_mut(),
+                ptr::null(),
+                winsize.as_ref().map_or(ptr::null(), ptr::from_ref),
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        let (master, slave) = unsafe { (File::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+        set_cloexec(&master)?;
+        set_cloexec(&slave)?;
+        disable_output_processing(&slave)?;
+
+        Ok(Self {
+            master: PtyReader { master },
+            slave,
+        })
+    }
+}
+
+impl Read for PtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.master.read(buf) {
+            // Linux reports EIO once all slave descriptors are closed
+            Err(error) if error.raw_os_error() == Some(libc::EIO) => Ok(0),
+            result => result,
+        }
+    }
+}
+
+pub fn terminal_width(terminal: &impl AsRawFd) -> Option<usize> {
+    window_size(terminal)
+        .map(|winsize| usize::from(winsize.ws_col))
+        .filter(|&width| width > 0)
+}
+
+fn window_size(terminal: &impl AsRawFd) -> Option<libc::winsize> {
+    let terminal = terminal.as_raw_fd();
+    unsafe {
+        let mut winsize = MaybeUninit::<libc::winsize>::zeroed();
+        (libc::ioctl(terminal, libc::TIOCGWINSZ, winsize.as_mut_ptr()) == 0)
+            .then(|| winsize.assume_init())
+    }
+}
+
+// Otherwise `\n` is translated into `\r\n`
+fn disable_output_processing(fd: &impl AsRawFd) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+    let result = unsafe {
+        let mut termios = MaybeUninit::<libc::termios>::zeroed();
+        if libc::tcgetattr(fd, termios.as_mut_ptr()) == 0 {
+            let mut termios = termios.assume_init();
+            termios.c_oflag &= !libc::OPOST;
+            libc::tcsetattr(fd, libc::TCSANOW, &termios)
+        } else {
+            -1
+        }
+    };
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn set_cloexec(fd: &impl AsRawFd) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+    let result = unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+        libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC)
+    };
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
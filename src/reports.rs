@@ -0,0 +1,256 @@
+use crate::{
+    models::{Level, Location},
+    options::Options,
+};
+use anyhow::{Context, Result};
+use cargo_metadata::CompilerMessage;
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+const CHECKSTYLE_VERSION: &str = "4.3";
+const RUSTC: &str = "rustc";
+
+struct Entry<'m> {
+    line: usize,
+    column: usize,
+    severity: &'static str,
+    source: &'m str,
+    message: &'m str,
+}
+
+pub fn write(
+    errors: &[(CompilerMessage, Location)],
+    warnings: &[(CompilerMessage, Location)],
+    options: &Options,
+    workspace_root: &Path,
+) -> Result<()> {
+    let no_reports = options.checkstyle_report.is_none() && options.junit_report.is_none();
+    if no_reports || options.help || options.version {
+        return Ok(());
+    }
+
+    let files = group_by_files(errors.iter().chain(warnings), workspace_root);
+    if let Some(path) = &options.checkstyle_report {
+        write_report(path, &checkstyle(&files))?;
+    }
+    if let Some(path) = &options.junit_report {
+        write_report(path, &junit(&files))?;
+    }
+    Ok(())
+}
+
+fn group_by_files<'m>(
+    messages: impl Iterator<Item = &'m (CompilerMessage, Location)>,
+    workspace_root: &Path,
+) -> BTreeMap<String, Vec<Entry<'m>>> {
+    let mut result = BTreeMap::<String, Vec<Entry>>::new();
+    for (message, location) in messages {
+        let path = location
+            .path
+            .strip_prefix(workspace_root)
+            .unwrap_or(&location.path)
+            .to_string_lossy()
+            .to_string();
+        let source = message
+            .message
+            .code
+            .as_ref()
+            .map(|code| code.code.as_str())
+            .unwrap_or(RUSTC);
+        result.entry(path).or_default().push(Entry {
+            line: location.line,
+            column: location.column,
+            severity: severity(location.level),
+            source,
+            message: &message.message.message,
+        });
+    }
+    result
+}
+
+fn checkstyle(files: &BTreeMap<String, Vec<Entry>>) -> String {
+    let mut result = String::new();
+    result.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(result, "<checkstyle version=\"{CHECKSTYLE_VERSION}\">");
+    for (path, entries) in files {
+        let _ = writeln!(result, "  <file name=\"{}\">", escape(path));
+        for entry in entries {
+            let _ = writeln!(
+                result,
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" \
+                 source=\"{}\"/>",
+                entry.line,
+                entry.column,
+                entry.severity,
+                escape(entry.message),
+                escape(entry.source),
+            );
+        }
+        result.push_str("  </file>\n");
+    }
+    result.push_str("</checkstyle>\n");
+    result
+}
+
+fn junit(files: &BTreeMap<String, Vec<Entry>>) -> String {
+    const NAME: &str = env!("CARGO_PKG_NAME");
+
+    let mut result = String::new();
+    result.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        result,
+        "<testsuites name=\"{NAME}\" tests=\"{0}\" failures=\"{0}\">",
+        files.len()
+    );
+    let _ = writeln!(
+        result,
+        "  <testsuite name=\"{NAME}\" tests=\"{0}\" failures=\"{0}\" errors=\"0\">",
+        files.len()
+    );
+    for (path, entries) in files {
+        let errors = entries.iter().filter(|i| i.severity == "error").count();
+        let warnings = entries.iter().filter(|i| i.severity == "warning").count();
+        let failure_type = if errors > 0 { "error" } else { "warning" };
+        let details = entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{path}:{}:{}: {}[{}]: {}",
+                    entry.line, entry.column, entry.severity, entry.source, entry.message
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let path = escape(path);
+        let _ = writeln!(
+            result,
+            "    <testcase name=\"{path}\" classname=\"{NAME}\" file=\"{path}\">"
+        );
+        let _ = writeln!(
+            result,
+            "      <failure type=\"{failure_type}\" message=\"{errors} error(s), {warnings} \
+             warning(s)\">{}</failure>",
+            escape(&details)
+        );
+        result.push_str("    </testcase>\n");
+    }
+    result.push_str("  </testsuite>\n");
+    result.push_str("</testsuites>\n");
+    result
+}
+
+fn write_report(path: &Path, report: &str) -> Result<()> {
+    fs::write(path, report).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn severity(level: Level) -> &'static str {
+    match level {
+        Level::Ice | Level::Error | Level::TestFailure => "error",
+        Level::Warning => "warning",
+        _ => "info",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for i in text.chars() {
+        match i {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            '\n' => result.push_str("&#10;"),
+            '\t' => result.push_str("&#9;"),
+            '\r' => result.push_str("&#13;"),
+            i if i.is_control() => (),
+            i => result.push(i),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn files() -> BTreeMap<String, Vec<Entry<'static>>> {
+        BTreeMap::from([
+            (
+                "src/a&b.rs".to_owned(),
+                vec![
+                    Entry {
+                        line: 1,
+                        column: 2,
+                        severity: "error",
+                        source: "E0308",
+                        message: "expected `<T>`, found \"&str\"",
+                    },
+                    Entry {
+                        line: 3,
+                        column: 4,
+                        severity: "warning",
+                        source: "rustc",
+                        message: "it's\n\tunused\u{7}",
+                    },
+                ],
+            ),
+            (
+                "src/main.rs".to_owned(),
+                vec![Entry {
+                    line: 5,
+                    column: 6,
+                    severity: "warning",
+                    source: "clippy::needless_return",
+                    message: "unneeded `return` statement",
+                }],
+            ),
+        ])
+    }
+
+    #[test]
+    fn escape_xml() {
+        assert_eq!(
+            escape("<a href=\"x\">'&'</a>\r\n\t\u{0}"),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;&#13;&#10;&#9;"
+        );
+    }
+
+    #[test]
+    fn checkstyle_report() {
+        assert_eq!(
+            checkstyle(&files()),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="src/a&amp;b.rs">
+    <error line="1" column="2" severity="error" message="expected `&lt;T&gt;`, found &quot;&amp;str&quot;" source="E0308"/>
+    <error line="3" column="4" severity="warning" message="it&apos;s&#10;&#9;unused" source="rustc"/>
+  </file>
+  <file name="src/main.rs">
+    <error line="5" column="6" severity="warning" message="unneeded `return` statement" source="clippy::needless_return"/>
+  </file>
+</checkstyle>
+"#
+        );
+    }
+
+    #[test]
+    fn junit_report() {
+        assert_eq!(
+            junit(&files()),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="cargo-limit" tests="2" failures="2">
+  <testsuite name="cargo-limit" tests="2" failures="2" errors="0">
+    <testcase name="src/a&amp;b.rs" classname="cargo-limit" file="src/a&amp;b.rs">
+      <failure type="error" message="1 error(s), 1 warning(s)">src/a&amp;b.rs:1:2: error[E0308]: expected `&lt;T&gt;`, found &quot;&amp;str&quot;&#10;src/a&amp;b.rs:3:4: warning[rustc]: it&apos;s&#10;&#9;unused</failure>
+    </testcase>
+    <testcase name="src/main.rs" classname="cargo-limit" file="src/main.rs">
+      <failure type="warning" message="0 error(s), 1 warning(s)">src/main.rs:5:6: warning[clippy::needless_return]: unneeded `return` statement</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
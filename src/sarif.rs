@@ -0,0 +1,425 @@
+use crate::{
+    models::Location,
+    uri::{encode_path, path_to_uri},
+};
+use cargo_metadata::{
+    CompilerMessage,
+    diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan},
+};
+use itertools::Itertools;
+use serde_json::{Value, json};
+use std::path::Path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const VERSION: &str = "2.1.0";
+const SRCROOT: &str = "%SRCROOT%";
+const CLIPPY_LINT_PREFIX: &str = "clippy::";
+
+pub fn log(messages: &[(CompilerMessage, Location)], workspace_root: &Path) -> Value {
+    let rules = messages
+        .iter()
+        .filter_map(|(message, _)| message.message.code.as_ref())
+        .map(|code| code.code.as_str())
+        .unique()
+        .sorted()
+        .map(rule)
+        .collect::<Vec<_>>();
+    let results = messages
+        .iter()
+        .map(|(message, location)| result(&message.message, location, workspace_root))
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            "originalUriBaseIds": {
+                SRCROOT: { "uri": format!("{}/", path_to_uri(workspace_root)) },
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn rule(code: &str) -> Value {
+    let help_uri = if let Some(lint) = code.strip_prefix(CLIPPY_LINT_PREFIX) {
+        Some(format!(
+            "https://rust-lang.github.io/rust-clippy/master/index.html#{lint}"
+        ))
+    } else if code
+        .strip_prefix('E')
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|i| i.is_ascii_digit()))
+    {
+        Some(format!("https://doc.rust-lang.org/error_codes/{code}.html"))
+    } else {
+        None
+    };
+
+    match help_uri {
+        Some(help_uri) => json!({ "id": code, "helpUri": help_uri }),
+        None => json!({ "id": code }),
+    }
+}
+
+fn result(diagnostic: &Diagnostic, location: &Location, workspace_root: &Path) -> Value {
+    let span = find_span(diagnostic, location);
+    let region = match span {
+        Some(span) => region(span),
+        None => json!({ "startLine": location.line, "startColumn": location.column }),
+    };
+    let fixes = diagnostic
+        .children
+        .iter()
+        .chain([diagnostic])
+        .filter_map(|i| fix(i, workspace_root))
+        .collect::<Vec<_>>();
+
+    let mut result = json!({
+        "level": level(diagnostic.level),
+        "message": { "text": diagnostic.message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": artifact_location(&location.path, workspace_root),
+                "region": region,
+            },
+        }],
+    });
+    if let Some(code) = &diagnostic.code {
+        result["ruleId"] = json!(code.code);
+    }
+    if !fixes.is_empty() {
+        result["fixes"] = json!(fixes);
+    }
+    result
+}
+
+fn fix(diagnostic: &Diagnostic, workspace_root: &Path) -> Option<Value> {
+    let artifact_changes = diagnostic
+        .spans
+        .iter()
+        .filter_map(|span| {
+            span.suggested_replacement
+                .as_ref()
+                .map(|replacement| (span, replacement))
+        })
+        .into_group_map_by(|(span, _)| span.file_name.clone())
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(file_name, replacements)| {
+            let path = workspace_root.join(file_name);
+            let replacements = replacements
+                .into_iter()
+                .map(|(span, replacement)| {
+                    json!({
+                        "deletedRegion": region(span),
+                        "insertedContent": { "text": replacement },
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "artifactLocation": artifact_location(&path, workspace_root),
+                "replacements": replacements,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if artifact_changes.is_empty() {
+        None
+    } else {
+        Some(json!({
+            "description": { "text": diagnostic.message },
+            "artifactChanges": artifact_changes,
+        }))
+    }
+}
+
+fn find_span<'d>(diagnostic: &'d Diagnostic, location: &Location) -> Option<&'d DiagnosticSpan> {
+    diagnostic
+        .spans
+        .iter()
+        .filter(|span| span.is_primary)
+        .flat_map(|span| {
+            let mut expansions = vec![span];
+            let mut span = span;
+            while let Some(expansion) = &span.expansion {
+                span = &expansion.span;
+                expansions.push(span);
+            }
+            expansions
+        })
+        .find(|span| span.line_start == location.line && span.column_start == location.column)
+}
+
+fn region(span: &DiagnosticSpan) -> Value {
+    json!({
+        "startLine": span.line_start,
+        "startColumn": span.column_start,
+        "endLine": span.line_end,
+        "endColumn": span.column_end,
+    })
+}
+
+fn artifact_location(path: &Path, workspace_root: &Path) -> Value {
+    match path.strip_prefix(workspace_root) {
+        Ok(relative_path) => json!({ "uri": encode_path(relative_path), "uriBaseId": SRCROOT }),
+        Err(_) => json!({ "uri": path_to_uri(path) }),
+    }
+}
+
+fn level(level: DiagnosticLevel) -> &'static str {
+    match level {
+        DiagnosticLevel::Ice | DiagnosticLevel::Error => "error",
+        DiagnosticLevel::Warning => "warning",
+        DiagnosticLevel::Note | DiagnosticLevel::Help => "note",
+        _ => "none",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Level;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    const WORKSPACE_ROOT: &str = "/workspace";
+
+    fn span(line: usize, column: usize, replacement: Option<&str>) -> Value {
+        json!({
+            "file_name": "src/main.rs",
+            "byte_start": 0,
+            "byte_end": 1,
+            "line_start": line,
+            "line_end": line,
+            "column_start": column,
+            "column_end": column + 1,
+            "is_primary": true,
+            "text": [],
+            "label": null,
+            "suggested_replacement": replacement,
+            "suggestion_applicability": replacement.map(|_| "MachineApplicable"),
+            "expansion": null,
+        })
+    }
+
+    fn message(
+        level: &str,
+        text: &str,
+        code: Option<&str>,
+        spans: Vec<Value>,
+        children: Vec<Value>,
+    ) -> CompilerMessage {
+        serde_json::from_value(json!({
+            "reason": "compiler-message",
+            "package_id": "path+file:///workspace#app@0.1.0",
+            "manifest_path": "/workspace/Cargo.toml",
+            "target": {
+                "name": "app",
+                "kind": ["bin"],
+                "crate_types": ["bin"],
+                "src_path": "/workspace/src/main.rs",
+                "edition": "2024",
+                "doctest": false,
+                "test": true,
+                "doc": true,
+            },
+            "message": {
+                "$message_type": "diagnostic",
+                "message": text,
+                "code": code.map(|code| json!({ "code": code, "explanation": null })),
+                "level": level,
+                "spans": spans,
+                "children": children,
+                "rendered": null,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn location(path: &str, line: usize, column: usize, level: Level) -> Location {
+        Location {
+            path: PathBuf::from(path),
+            line,
+            column,
+            message: String::new(),
+            level,
+            label: None,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rules_with_help_uri() {
+        assert_eq!(
+            [
+                "E0308",
+                "E",
+                "Eabc",
+                "clippy::needless_return",
+                "unused_variables"
+            ]
+            .map(rule),
+            [
+                json!({
+                    "id": "E0308",
+                    "helpUri": "https://doc.rust-lang.org/error_codes/E0308.html",
+                }),
+                json!({ "id": "E" }),
+                json!({ "id": "Eabc" }),
+                json!({
+                    "id": "clippy::needless_return",
+                    "helpUri": "https://rust-lang.github.io/rust-clippy/master/index.html#needless_return",
+                }),
+                json!({ "id": "unused_variables" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn run() {
+        let workspace_root = Path::new(WORKSPACE_ROOT);
+        let suggestion = json!({
+            "message": "remove `return`",
+            "code": null,
+            "level": "help",
+            "spans": [span(2, 5, Some("1"))],
+            "children": [],
+            "rendered": null,
+        });
+        let messages = [
+            (
+                message(
+                    "error",
+                    "mismatched types",
+                    Some("E0308"),
+                    vec![span(4, 13, None)],
+                    Vec::new(),
+                ),
+                location("/workspace/src/main.rs", 4, 13, Level::Error),
+            ),
+            (
+                message(
+                    "warning",
+                    "unneeded `return` statement",
+                    Some("clippy::needless_return"),
+                    vec![span(2, 5, None)],
+                    vec![suggestion],
+                ),
+                location("/workspace/src/main.rs", 2, 5, Level::Warning),
+            ),
+            (
+                message("note", "dependency note", None, Vec::new(), Vec::new()),
+                location("/registry/dep 1/src/lib.rs", 7, 3, Level::Note),
+            ),
+        ];
+
+        assert_eq!(
+            log(&messages, workspace_root),
+            json!({
+                "$schema": SCHEMA,
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "cargo-limit",
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                            "rules": [
+                                {
+                                    "id": "E0308",
+                                    "helpUri": "https://doc.rust-lang.org/error_codes/E0308.html",
+                                },
+                                {
+                                    "id": "clippy::needless_return",
+                                    "helpUri": "https://rust-lang.github.io/rust-clippy/master/index.html#needless_return",
+                                },
+                            ],
+                        },
+                    },
+                    "originalUriBaseIds": {
+                        "%SRCROOT%": { "uri": "file:///workspace/" },
+                    },
+                    "results": [
+                        {
+                            "ruleId": "E0308",
+                            "level": "error",
+                            "message": { "text": "mismatched types" },
+                            "locations": [{
+                                "physicalLocation": {
+                                    "artifactLocation": {
+                                        "uri": "src/main.rs",
+                                        "uriBaseId": "%SRCROOT%",
+                                    },
+                                    "region": {
+                                        "startLine": 4,
+                                        "startColumn": 13,
+                                        "endLine": 4,
+                                        "endColumn": 14,
+                                    },
+                                },
+                            }],
+                        },
+                        {
+                            "ruleId": "clippy::needless_return",
+                            "level": "warning",
+                            "message": { "text": "unneeded `return` statement" },
+                            "locations": [{
+                                "physicalLocation": {
+                                    "artifactLocation": {
+                                        "uri": "src/main.rs",
+                                        "uriBaseId": "%SRCROOT%",
+                                    },
+                                    "region": {
+                                        "startLine": 2,
+                                        "startColumn": 5,
+                                        "endLine": 2,
+                                        "endColumn": 6,
+                                    },
+                                },
+                            }],
+                            "fixes": [{
+                                "description": { "text": "remove `return`" },
+                                "artifactChanges": [{
+                                    "artifactLocation": {
+                                        "uri": "src/main.rs",
+                                        "uriBaseId": "%SRCROOT%",
+                                    },
+                                    "replacements": [{
+                                        "deletedRegion": {
+                                            "startLine": 2,
+                                            "startColumn": 5,
+                                            "endLine": 2,
+                                            "endColumn": 6,
+                                        },
+                                        "insertedContent": { "text": "1" },
+                                    }],
+                                }],
+                            }],
+                        },
+                        {
+                            "level": "note",
+                            "message": { "text": "dependency note" },
+                            "locations": [{
+                                "physicalLocation": {
+                                    "artifactLocation": {
+                                        "uri": "file:///registry/dep%201/src/lib.rs",
+                                    },
+                                    "region": { "startLine": 7, "startColumn": 3 },
+                                },
+                            }],
+                        },
+                    ],
+                }],
+            })
+        );
+    }
+}
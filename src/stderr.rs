@@ -0,0 +1,389 @@
+use crate::{
+    build_script::{BuildScriptFailure, BuildScriptFailures, BuildScriptWarning},
+    io::{self, StatusLine, strip_ansi_escape_codes},
+    models::Location,
+    options::Options,
+    panic::{Panic, PanicParser},
+};
+use cargo_metadata::Metadata;
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt,
+    io::{BufRead, BufReader, IsTerminal, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+// Right-aligned verbs of cargo's progress lines
+const STATUS_VERBS: &[&str] = &[
+    "Adding",
+    "Blocking",
+    "Building",
+    "Checking",
+    "Compiling",
+    "Documenting",
+    "Downloaded",
+    "Downloading",
+    "Fresh",
+    "Locking",
+    "Updating",
+];
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Progress {
+    #[default]
+    Show,
+    Collapse,
+    Hide,
+}
+
+#[derive(Debug)]
+pub struct InvalidProgress(String);
+
+pub struct StderrParser {
+    progress: Progress,
+    status_line: StatusLine,
+    panic_parser: Option<PanicParser>,
+    build_script_warnings: Vec<BuildScriptWarning>,
+    build_script_failures: BuildScriptFailures,
+}
+
+#[derive(Default)]
+pub struct StderrOutput {
+    pub panics: Vec<Panic>,
+    pub build_script_warnings: Vec<BuildScriptWarning>,
+    pub build_script_failures: Vec<BuildScriptFailure>,
+}
+
+/// Locations found in cargo's stderr; warnings are yet to be filtered like rustc ones.
+#[derive(Default, Debug)]
+pub struct StderrLocations {
+    pub build_script_failures: Vec<Location>,
+    pub panics: Vec<Location>,
+    pub build_script_warnings: Vec<Location>,
+}
+
+impl StderrParser {
+    pub fn new(options: &Options, status_line: StatusLine) -> Self {
+        let progress = if options.progress == Progress::Collapse && !std::io::stderr().is_terminal()
+        {
+            Progress::Hide
+        } else {
+            options.progress
+        };
+        Self {
+            progress,
+            status_line,
+            panic_parser: options.shows_app_panics().then(PanicParser::default),
+            build_script_warnings: Vec::new(),
+            build_script_failures: BuildScriptFailures::default(),
+        }
+    }
+
+    pub fn copy(
+        mut self,
+        reader: impl Read,
+        writer: &mut impl Write,
+    ) -> std::io::Result<StderrOutput> {
+        let mut reader = BufReader::new(reader);
+        if self.progress == Progress::Show {
+            io::copy_lines(&mut reader, writer, |line| self.parse_line(line))?;
+            return self.finish(writer);
+        }
+
+        // Lines are written as soon as they can't be status lines anymore, so that prompts
+        // without new line are visible; cargo redraws its progress bar with \r
+        let mut line = Vec::new();
+        let mut written = 0;
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            for (index, &byte) in chunk.iter().enumerate() {
+                line.push(byte);
+                let is_line_end =
+                    byte == b'\n' || (byte == b'\r' && chunk.get(index + 1) != Some(&b'\n'));
+                if is_line_end {
+                    self.write_line_rest(&line, written, writer)?;
+                    line.clear();
+                    written = 0;
+                }
+            }
+            let consumed = chunk.len();
+            reader.consume(consumed);
+
+            if written < line.len()
+                && (written > 0 || !may_become_status_line(&String::from_utf8_lossy(&line)))
+            {
+                self.status_line.clear(writer)?;
+                writer.write_all(&line[written..])?;
+                written = line.len();
+            }
+        }
+        if !line.is_empty() {
+            self.write_line_rest(&line, written, writer)?;
+        }
+        self.finish(writer)
+    }
+
+    pub fn write_line(&mut self, line: &str, writer: &mut impl Write) -> std::io::Result<()> {
+        self.parse_line(line);
+        let text = strip_ansi_escape_codes(line.trim_end_matches(['\r', '\n']));
+        if is_status_line(&text) {
+            match self.progress {
+                Progress::Show => writer.write_all(line.as_bytes()),
+                Progress::Collapse => self.status_line.show(writer, text.trim_start()),
+                Progress::Hide => Ok(()),
+            }
+        } else {
+            self.status_line.clear(writer)?;
+            writer.write_all(line.as_bytes())
+        }
+    }
+
+    pub fn finish(self, writer: &mut impl Write) -> std::io::Result<StderrOutput> {
+        self.status_line.clear(writer)?;
+        Ok(StderrOutput {
+            panics: self
+                .panic_parser
+                .map(PanicParser::finish)
+                .unwrap_or_default(),
+            build_script_warnings: self.build_script_warnings,
+            build_script_failures: self.build_script_failures.finish(),
+        })
+    }
+
+    // Beginning of the line may be already written, if it's not a status line
+    fn write_line_rest(
+        &mut self,
+        line: &[u8],
+        written: usize,
+        writer: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let text = String::from_utf8_lossy(line);
+        if written == 0 {
+            self.write_line(&text, writer)
+        } else {
+            self.parse_line(&text);
+            writer.write_all(&line[written..])
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        if let Some(panic_parser) = &mut self.panic_parser {
+            panic_parser.push_line(line);
+        }
+        let text = strip_ansi_escape_codes(line.trim_end_matches(['\r', '\n']));
+        if let Some(warning) = BuildScriptWarning::parse(&text) {
+            self.build_script_warnings.push(warning);
+        }
+        self.build_script_failures.push_line(&text);
+    }
+}
+
+impl StderrOutput {
+    pub fn merge(&mut self, other: Self) {
+        self.panics.extend(other.panics);
+        self.build_script_warnings
+            .extend(other.build_script_warnings);
+        self.build_script_failures
+            .extend(other.build_script_failures);
+    }
+
+    pub fn into_locations(self, metadata: &Metadata, current_dir: &Path) -> StderrLocations {
+        let workspace_root = metadata.workspace_root.as_std_path();
+        StderrLocations {
+            build_script_failures: self
+                .build_script_failures
+                .into_iter()
+                .flat_map(|failure| failure.into_locations(workspace_root))
+                .collect(),
+            panics: self
+                .panics
+                .into_iter()
+                .flat_map(|panic| panic.into_locations(workspace_root, current_dir))
+                .collect(),
+            build_script_warnings: self
+                .build_script_warnings
+                .into_iter()
+                .filter_map(|warning| warning.into_location(metadata))
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for Progress {
+    type Err = InvalidProgress;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "show" => Ok(Self::Show),
+            "collapse" => Ok(Self::Collapse),
+            "hide" => Ok(Self::Hide),
+            _ => Err(InvalidProgress(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for InvalidProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid progress '{}', expected one of: show, collapse, hide",
+            self.0
+        )
+    }
+}
+
+impl Error for InvalidProgress {}
+
+// Incomplete line which starts like "   Compil"
+fn may_become_status_line(line: &str) -> bool {
+    let text = strip_ansi_escape_codes(line);
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        return true;
+    }
+    if trimmed.len() == text.len() {
+        return false;
+    }
+    match trimmed.split_once(' ') {
+        Some((verb, _)) => STATUS_VERBS.contains(&verb),
+        None => STATUS_VERBS.iter().any(|verb| verb.starts_with(trimmed)),
+    }
+}
+
+fn is_status_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.len() < line.len()
+        && trimmed
+            .split_once(' ')
+            .is_some_and(|(verb, _)| STATUS_VERBS.contains(&verb))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Level;
+    use cargo_metadata::MetadataCommand;
+    use pretty_assertions::assert_eq;
+    use std::{io::pipe, thread};
+
+    const CLEAR_LINE: &str = "\r\x1b[K";
+
+    fn parser(progress: Progress) -> StderrParser {
+        StderrParser {
+            progress,
+            status_line: StatusLine::default(),
+            panic_parser: Some(PanicParser::default()),
+            build_script_warnings: Vec::new(),
+            build_script_failures: BuildScriptFailures::default(),
+        }
+    }
+
+    #[test]
+    fn collapse_progress_lines() -> std::io::Result<()> {
+        let stderr = "   Compiling app v0.1.0
+    Building [==>  ] 1/2: app\r    Building [====>] 2/2: app\rwarning: unused variable
+";
+        let mut output = Vec::new();
+        parser(Progress::Collapse).copy(stderr.as_bytes(), &mut output)?;
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            format!(
+                "{CLEAR_LINE}Compiling app v0.1.0{CLEAR_LINE}Building [==>  ] 1/2: \
+                 app{CLEAR_LINE}Building [====>] 2/2: app{CLEAR_LINE}warning: unused variable\n"
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pass_partial_lines_through() -> std::io::Result<()> {
+        let (input_reader, mut input_writer) = pipe()?;
+        let (mut output_reader, mut output_writer) = pipe()?;
+        let copying =
+            thread::spawn(move || parser(Progress::Hide).copy(input_reader, &mut output_writer));
+
+        input_writer.write_all(b"   Compiling app v0.1.0\n> ")?;
+        let mut prompt = [0; 2];
+        output_reader.read_exact(&mut prompt)?;
+        assert_eq!(&prompt, b"> ");
+
+        input_writer.write_all(b"answer\n   Finished\n")?;
+        drop(input_writer);
+        copying.join().unwrap()?;
+        let mut rest = String::new();
+        output_reader.read_to_string(&mut rest)?;
+        assert_eq!(rest, "answer\n   Finished\n");
+        Ok(())
+    }
+
+    #[test]
+    fn into_locations() -> anyhow::Result<()> {
+        let metadata = MetadataCommand::new()
+            .manifest_path(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .no_deps()
+            .exec()?;
+        let workspace_root = metadata.workspace_root.as_std_path();
+        let stderr = format!(
+            "   Compiling {package} v{version} ({root})
+warning: {package}@{version}: src/models.rs:3:7: warning: unused variable 'x'
+warning: other@0.1.0: src/models.rs:3:7: warning: not in workspace
+error: failed to run custom build command for `{package} v{version} ({root})`
+
+Caused by:
+  process didn't exit successfully: `build-script-build` (exit status: 1)
+  --- stdout
+  cargo:warning=src/io.rs:2:11: error: expected ';' before '}}' token
+
+thread 'main' (385) panicked at src/panic.rs:8:9:
+boom
+",
+            package = env!("CARGO_PKG_NAME"),
+            version = env!("CARGO_PKG_VERSION"),
+            root = workspace_root.display(),
+        );
+
+        let mut output = Vec::new();
+        let locations = parser(Progress::Show)
+            .copy(stderr.as_bytes(), &mut output)?
+            .into_locations(&metadata, workspace_root);
+        assert_eq!(String::from_utf8_lossy(&output), stderr);
+
+        let brief = |locations: Vec<Location>| {
+            locations
+                .into_iter()
+                .map(|location| {
+                    (
+                        location
+                            .path
+                            .strip_prefix(workspace_root)
+                            .unwrap()
+                            .to_string_lossy()
+                            .replace('\\', "/"),
+                        location.line,
+                        location.column,
+                        location.level,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            brief(locations.build_script_failures),
+            [("src/io.rs".to_owned(), 2, 11, Level::Error)]
+        );
+        assert_eq!(
+            brief(locations.panics),
+            [("src/panic.rs".to_owned(), 8, 9, Level::Error)]
+        );
+        assert_eq!(
+            brief(locations.build_script_warnings),
+            [("src/models.rs".to_owned(), 3, 7, Level::Warning)]
+        );
+        Ok(())
+    }
+}
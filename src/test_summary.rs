@@ -0,0 +1,215 @@
+use crate::io::{Buffers, strip_ansi_escape_codes};
+use std::{io, path::Path};
+
+const RUNNING_PREFIX: &str = "Running ";
+const DOC_TESTS_PREFIX: &str = "Doc-tests ";
+const RUNNING_TESTS_PREFIX: &str = "running ";
+const DOC_TESTS_TIMING_PREFIX: &str = "all doctests ran in ";
+const TEST_PREFIX: &str = "test ";
+const TEST_RESULT_PREFIX: &str = "test result: ";
+const TEST_FAILED_SUFFIX: &str = " FAILED";
+const FAILURES_HEADER: &str = "failures:";
+
+#[derive(Default)]
+pub struct TestSummary {
+    current_binary: Option<String>,
+    binaries: Vec<TestBinary>,
+    failures_are_shown: bool,
+}
+
+struct TestBinary {
+    name: String,
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+}
+
+impl TestSummary {
+    /// Returns `false` for passed and ignored tests, as well as for libtest's own progress lines.
+    pub fn is_visible(&mut self, line: &str) -> bool {
+        let line = strip_ansi_escape_codes(line.trim_end_matches(['\r', '\n']));
+        // cargo's status lines are right-aligned, unlike apps' output
+        let trimmed = line.trim_start();
+        let trimmed = if trimmed.len() < line.len() {
+            trimmed
+        } else {
+            ""
+        };
+
+        if let Some(binary) = trimmed.strip_prefix(RUNNING_PREFIX) {
+            self.current_binary = Some(parse_binary_name(binary));
+            false
+        } else if let Some(package) = trimmed.strip_prefix(DOC_TESTS_PREFIX) {
+            self.current_binary = Some(format!("{package} (doc-tests)"));
+            false
+        } else if let Some(result) = line.strip_prefix(TEST_RESULT_PREFIX) {
+            self.failures_are_shown = false;
+            self.binaries.push(TestBinary::parse(
+                self.current_binary.take().unwrap_or_default(),
+                result,
+            ));
+            false
+        } else if line == FAILURES_HEADER {
+            self.failures_are_shown = true;
+            true
+        } else if self.failures_are_shown {
+            true
+        } else if line.is_empty()
+            || line.starts_with(RUNNING_TESTS_PREFIX)
+            || line.starts_with(DOC_TESTS_TIMING_PREFIX)
+        {
+            false
+        } else if line.starts_with(TEST_PREFIX) && line.contains(" ... ") {
+            line.ends_with(TEST_FAILED_SUFFIX)
+        } else {
+            true
+        }
+    }
+
+    pub fn write(&self, buffers: &mut Buffers) -> io::Result<()> {
+        if self.binaries.is_empty() {
+            return Ok(());
+        }
+
+        let total = TestBinary {
+            name: "total".to_owned(),
+            passed: self.binaries.iter().map(|i| i.passed).sum(),
+            failed: self.binaries.iter().map(|i| i.failed).sum(),
+            ignored: self.binaries.iter().map(|i| i.ignored).sum(),
+        };
+        buffers.writeln_to_stdout("")?;
+        buffers.writeln_to_stdout("test summary:")?;
+        buffers.writeln_to_stdout(&format!(
+            "{:>8} {:>8} {:>8}  binary",
+            "passed", "failed", "ignored"
+        ))?;
+        for binary in self.binaries.iter().chain([&total]) {
+            buffers.writeln_to_stdout(&format!(
+                "{:>8} {:>8} {:>8}  {}",
+                binary.passed, binary.failed, binary.ignored, binary.name
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+impl TestBinary {
+    // "ok. 1 passed; 2 failed; 3 ignored; 0 measured; 0 filtered out; finished in 0.02s"
+    fn parse(name: String, result: &str) -> Self {
+        let mut binary = Self {
+            name,
+            passed: 0,
+            failed: 0,
+            ignored: 0,
+        };
+        let counts = result.split_once(". ").map_or(result, |(_, counts)| counts);
+        for count in counts.split("; ") {
+            let Some((number, kind)) = count.split_once(' ') else {
+                continue;
+            };
+            let Ok(number) = number.parse() else {
+                continue;
+            };
+            match kind {
+                "passed" => binary.passed = number,
+                "failed" => binary.failed = number,
+                "ignored" => binary.ignored = number,
+                _ => (),
+            }
+        }
+        binary
+    }
+}
+
+// "unittests src/lib.rs (target/debug/deps/foo-0123456789abcdef)" -> "foo (unittests src/lib.rs)"
+fn parse_binary_name(running: &str) -> String {
+    let Some((target, path)) = running
+        .strip_suffix(')')
+        .and_then(|running| running.rsplit_once(" ("))
+    else {
+        return running.to_owned();
+    };
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = stem.rsplit_once('-').map_or(&*stem, |(name, _)| name);
+    format!("{name} ({target})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn several_binaries_and_doc_tests() {
+        let stdout = "     Running unittests src/lib.rs (target/debug/deps/app-0123456789abcdef)
+
+running 3 tests
+test a ... ok
+test b ... ignored
+test c ... FAILED
+
+failures:
+
+---- c stdout ----
+assertion failed
+
+failures:
+    c
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+     Running tests/cli.rs (target/debug/deps/cli-fedcba9876543210)
+
+running 1 test
+test cli ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 4 filtered out; finished in 0.01s
+
+   Doc-tests app
+
+running 2 tests
+test src/lib.rs - f (line 3) ... ok
+test src/lib.rs - g (line 9) ... ignored
+
+test result: ok. 1 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.10s
+all doctests ran in 0.20s; merged doctests compilation took 0.10s
+";
+
+        let mut summary = TestSummary::default();
+        let visible = stdout
+            .lines()
+            .filter(|line| summary.is_visible(line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            visible,
+            [
+                "test c ... FAILED",
+                "failures:",
+                "",
+                "---- c stdout ----",
+                "assertion failed",
+                "",
+                "failures:",
+                "    c",
+                "",
+            ]
+        );
+
+        let binaries = summary
+            .binaries
+            .iter()
+            .map(|i| (i.name.as_str(), i.passed, i.failed, i.ignored))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            binaries,
+            [
+                ("app (unittests src/lib.rs)", 1, 1, 1),
+                ("cli (tests/cli.rs)", 1, 0, 0),
+                ("app (doc-tests)", 1, 0, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+const FILE_URI_PREFIX: &str = "file://";
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = encode_path(path);
+    if path.starts_with('/') {
+        format!("{FILE_URI_PREFIX}{path}")
+    } else {
+        format!("{FILE_URI_PREFIX}/{path}")
+    }
+}
+
+pub fn encode_path(path: &Path) -> String {
+    let mut result = String::new();
+    for byte in path.to_string_lossy().replace('\\', "/").bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~:".contains(&byte) {
+            result.push(byte as char);
+        } else {
+            result.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    result
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix(FILE_URI_PREFIX)?;
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+
+    let is_windows_path = path.as_bytes().get(2) == Some(&b':');
+    let path = if is_windows_path {
+        path[1..].to_owned()
+    } else {
+        path
+    };
+    Some(PathBuf::from(path))
+}
@@ -0,0 +1,187 @@
+use crate::{io::Buffers, options::Options};
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+pub struct WarningBudget {
+    pub unique_workspace_warnings: usize,
+    pub has_errors: bool,
+    pub checks_part_of_workspace: bool,
+}
+
+impl WarningBudget {
+    pub fn check(
+        self,
+        buffers: &mut Buffers,
+        options: &Options,
+        workspace_root: &Path,
+    ) -> Result<bool> {
+        if options.help || options.version {
+            return Ok(true);
+        }
+
+        let budget_file = options
+            .warn_budget_file
+            .as_ref()
+            .map(|path| workspace_root.join(path));
+        let errors = self.check_budget(
+            options.warn_budget,
+            budget_file.as_deref(),
+            options.warn_ratchet,
+        )?;
+        for error in &errors {
+            buffers.write_to_stderr(format!("error: {error}\n"))?;
+        }
+        Ok(errors.is_empty())
+    }
+
+    // The file is written only when ratchet is enabled
+    // and the whole workspace is checked successfully
+    fn check_budget(
+        &self,
+        limit: Option<usize>,
+        budget_file: Option<&Path>,
+        ratchet: bool,
+    ) -> Result<Vec<String>> {
+        let mut errors = Vec::new();
+        if let Some(limit) = limit {
+            errors.extend(self.check_limit(limit, "limit"));
+        }
+
+        if let Some(path) = budget_file {
+            match Self::read_baseline(path)? {
+                Some(baseline) => {
+                    errors.extend(self.check_limit(baseline, "baseline"));
+                    let shrunk = self.unique_workspace_warnings < baseline;
+                    if ratchet && shrunk && self.counts_every_warning() {
+                        Self::write_baseline(path, self.unique_workspace_warnings)?;
+                    }
+                },
+                None if ratchet => {
+                    if self.counts_every_warning() {
+                        Self::write_baseline(path, self.unique_workspace_warnings)?;
+                    }
+                },
+                None => errors.push(format!(
+                    "{} doesn't exist, enable warnings ratchet to create it",
+                    path.display()
+                )),
+            }
+        }
+
+        Ok(errors)
+    }
+
+    fn counts_every_warning(&self) -> bool {
+        !self.has_errors && !self.checks_part_of_workspace
+    }
+
+    fn check_limit(&self, limit: usize, kind: &str) -> Option<String> {
+        let warnings = self.unique_workspace_warnings;
+        (warnings > limit).then(|| format!("{warnings} warnings exceed the {kind} of {limit}"))
+    }
+
+    fn read_baseline(path: &Path) -> Result<Option<usize>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path)?;
+        let baseline = text
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid {}", path.display()))?;
+        Ok(Some(baseline))
+    }
+
+    fn write_baseline(path: &Path, warnings: usize) -> Result<()> {
+        fs::write(path, format!("{warnings}\n"))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::env;
+
+    fn budget(unique_workspace_warnings: usize, has_errors: bool) -> WarningBudget {
+        WarningBudget {
+            unique_workspace_warnings,
+            has_errors,
+            checks_part_of_workspace: false,
+        }
+    }
+
+    fn part_of_workspace(unique_workspace_warnings: usize) -> WarningBudget {
+        WarningBudget {
+            checks_part_of_workspace: true,
+            ..budget(unique_workspace_warnings, false)
+        }
+    }
+
+    #[test]
+    fn limit() -> Result<()> {
+        assert_eq!(
+            budget(3, false).check_budget(Some(3), None, false)?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            budget(4, false).check_budget(Some(3), None, true)?,
+            ["4 warnings exceed the limit of 3"]
+        );
+        assert_eq!(
+            budget(4, false).check_budget(None, None, false)?,
+            Vec::<String>::new()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ratchet() -> Result<()> {
+        let dir = env::temp_dir().join(format!("cargo-limit-budget-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(".cargo-limit-warnings");
+        let stored = || fs::read_to_string(&path).ok();
+
+        let errors = budget(5, false).check_budget(None, Some(&path), false)?;
+        assert_eq!(
+            errors,
+            [format!(
+                "{} doesn't exist, enable warnings ratchet to create it",
+                path.display()
+            )]
+        );
+        assert_eq!(stored(), None);
+
+        budget(5, true).check_budget(None, Some(&path), true)?;
+        assert_eq!(stored(), None);
+        part_of_workspace(5).check_budget(None, Some(&path), true)?;
+        assert_eq!(stored(), None);
+        budget(5, false).check_budget(None, Some(&path), true)?;
+        assert_eq!(stored().as_deref(), Some("5\n"));
+
+        // Going up fails and keeps the budget
+        assert_eq!(
+            budget(6, false).check_budget(Some(10), Some(&path), true)?,
+            ["6 warnings exceed the baseline of 5"]
+        );
+        assert_eq!(stored().as_deref(), Some("5\n"));
+
+        // Going down shrinks it, unless ratchet is disabled, there are errors
+        // or not every package and target is checked
+        assert_eq!(
+            budget(4, false).check_budget(None, Some(&path), false)?,
+            Vec::<String>::new()
+        );
+        assert_eq!(stored().as_deref(), Some("5\n"));
+        budget(4, true).check_budget(None, Some(&path), true)?;
+        assert_eq!(stored().as_deref(), Some("5\n"));
+        part_of_workspace(4).check_budget(None, Some(&path), true)?;
+        assert_eq!(stored().as_deref(), Some("5\n"));
+        budget(4, false).check_budget(None, Some(&path), true)?;
+        assert_eq!(stored().as_deref(), Some("4\n"));
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
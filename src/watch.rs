@@ -0,0 +1,303 @@
+use crate::process::CargoProcess;
+use anyhow::Result;
+use cargo_metadata::Metadata;
+use ignore::gitignore::Gitignore;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::{
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
+
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+const INTERRUPTION_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+const CLEAR_SCREEN: &[u8] = b"\x1b[2J\x1b[3J\x1b[H";
+const GITIGNORE: &str = ".gitignore";
+const GIT_DIR: &str = ".git";
+const FAILED_RUN_EXIT_CODE: i32 = 1;
+
+pub struct Watcher {
+    _watcher: RecommendedWatcher,
+    changes: mpsc::Receiver<()>,
+}
+
+trait Changes {
+    fn recv_timeout(&self, timeout: Duration) -> Result<(), RecvTimeoutError>;
+}
+
+struct ChangesFilter {
+    target_directory: PathBuf,
+    gitignores: Vec<Gitignore>,
+}
+
+impl Watcher {
+    pub fn new(metadata: &Metadata) -> Result<Self> {
+        let dirs = watched_dirs(metadata);
+        let filter = ChangesFilter::new(metadata, &dirs);
+
+        let (sender, changes) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event
+                && filter.is_relevant(&event)
+            {
+                CargoProcess::kill_running();
+                let _ = sender.send(());
+            }
+        })?;
+        let workspace_root = metadata.workspace_root.as_std_path();
+        if !dirs.iter().any(|dir| workspace_root.starts_with(dir)) {
+            watcher.watch(workspace_root, RecursiveMode::NonRecursive)?;
+        }
+        for dir in dirs {
+            watcher.watch(&dir, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    pub fn run(self, mut run_cargo: impl FnMut() -> Result<i32>) -> Result<i32> {
+        loop {
+            clear_screen()?;
+            let exit_code = run_reporting_error(&mut run_cargo, &mut io::stderr())?;
+            if !wait_for_changes(&self.changes) {
+                return Ok(exit_code);
+            }
+        }
+    }
+}
+
+// Failed run (like unreadable config or broken Cargo.toml) may be fixed by the next change
+fn run_reporting_error(
+    run_cargo: &mut impl FnMut() -> Result<i32>,
+    stderr: &mut impl Write,
+) -> io::Result<i32> {
+    match run_cargo() {
+        Ok(exit_code) => Ok(exit_code),
+        Err(err) => {
+            writeln!(stderr, "error: {err:#}")?;
+            stderr.flush()?;
+            Ok(FAILED_RUN_EXIT_CODE)
+        },
+    }
+}
+
+fn wait_for_changes(changes: &impl Changes) -> bool {
+    loop {
+        if CargoProcess::interrupted() {
+            return false;
+        }
+        match changes.recv_timeout(INTERRUPTION_CHECK_INTERVAL) {
+            Ok(()) => break,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+    while changes.recv_timeout(DEBOUNCE_TIMEOUT).is_ok() {}
+    !CargoProcess::interrupted()
+}
+
+impl Changes for mpsc::Receiver<()> {
+    fn recv_timeout(&self, timeout: Duration) -> Result<(), RecvTimeoutError> {
+        mpsc::Receiver::recv_timeout(self, timeout)
+    }
+}
+
+impl ChangesFilter {
+    fn new(metadata: &Metadata, dirs: &[PathBuf]) -> Self {
+        let mut gitignore_dirs = dirs.to_vec();
+        gitignore_dirs.push(metadata.workspace_root.clone().into());
+        gitignore_dirs.sort();
+        gitignore_dirs.dedup();
+
+        let gitignores = gitignore_dirs
+            .into_iter()
+            .map(|dir| dir.join(GITIGNORE))
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                let (gitignore, error) = Gitignore::new(path);
+                error.is_none().then_some(gitignore)
+            })
+            .collect();
+        Self {
+            target_directory: metadata.target_directory.clone().into(),
+            gitignores,
+        }
+    }
+
+    fn is_relevant(&self, event: &Event) -> bool {
+        !matches!(event.kind, EventKind::Access(_))
+            && event.paths.iter().any(|path| self.is_relevant_path(path))
+    }
+
+    fn is_relevant_path(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        !path.starts_with(&self.target_directory)
+            && !path.components().any(|i| i.as_os_str() == GIT_DIR)
+            && !self.gitignores.iter().any(|gitignore| {
+                path.starts_with(gitignore.path())
+                    && gitignore
+                        .matched_path_or_any_parents(path, is_dir)
+                        .is_ignore()
+            })
+    }
+}
+
+fn watched_dirs(metadata: &Metadata) -> Vec<PathBuf> {
+    let mut dirs = metadata
+        .workspace_packages()
+        .into_iter()
+        .filter_map(|package| package.manifest_path.parent())
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut result: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if !result.iter().any(|parent| dir.starts_with(parent)) {
+            result.push(dir);
+        }
+    }
+    result
+}
+
+fn clear_screen() -> io::Result<()> {
+    let mut stderr = io::stderr();
+    if stderr.is_terminal() {
+        stderr.write_all(CLEAR_SCREEN)?;
+        stderr.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::format_err;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use pretty_assertions::assert_eq;
+    use std::{cell::RefCell, collections::VecDeque, env, fs};
+
+    // Results of receiving changes, in order; then the sender is gone
+    struct ScriptedChanges {
+        results: RefCell<VecDeque<Result<(), RecvTimeoutError>>>,
+        timeouts: RefCell<Vec<Duration>>,
+    }
+
+    impl ScriptedChanges {
+        fn new(results: impl IntoIterator<Item = Result<(), RecvTimeoutError>>) -> Self {
+            Self {
+                results: RefCell::new(results.into_iter().collect()),
+                timeouts: RefCell::default(),
+            }
+        }
+    }
+
+    impl Changes for ScriptedChanges {
+        fn recv_timeout(&self, timeout: Duration) -> Result<(), RecvTimeoutError> {
+            self.timeouts.borrow_mut().push(timeout);
+            let result = self.results.borrow_mut().pop_front();
+            result.unwrap_or(Err(RecvTimeoutError::Disconnected))
+        }
+    }
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Result<Self> {
+            let path = env::temp_dir().join(format!("cargo-limit-{name}-{}", std::process::id()));
+            fs::create_dir_all(&path)?;
+            Ok(Self(path))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn keep_watching_after_error() -> Result<()> {
+        let mut stderr = Vec::new();
+        let exit_code = run_reporting_error(
+            &mut || Err(format_err!("failed to parse manifest").context("invalid Cargo.toml")),
+            &mut stderr,
+        )?;
+        assert_eq!(exit_code, FAILED_RUN_EXIT_CODE);
+        assert_eq!(
+            String::from_utf8_lossy(&stderr),
+            "error: invalid Cargo.toml: failed to parse manifest\n"
+        );
+        assert_eq!(run_reporting_error(&mut || Ok(101), &mut stderr)?, 101);
+        Ok(())
+    }
+
+    #[test]
+    fn debounce() {
+        use RecvTimeoutError::{Disconnected, Timeout};
+
+        // Returns after the last change is followed by a quiet period
+        let changes = ScriptedChanges::new([Err(Timeout), Ok(()), Ok(()), Ok(()), Err(Timeout)]);
+        assert!(wait_for_changes(&changes));
+        assert_eq!(
+            changes.timeouts.take(),
+            [
+                INTERRUPTION_CHECK_INTERVAL,
+                INTERRUPTION_CHECK_INTERVAL,
+                DEBOUNCE_TIMEOUT,
+                DEBOUNCE_TIMEOUT,
+                DEBOUNCE_TIMEOUT,
+            ]
+        );
+
+        // Watcher is gone while changes are debounced
+        let changes = ScriptedChanges::new([Ok(()), Ok(())]);
+        assert!(wait_for_changes(&changes));
+        assert_eq!(changes.timeouts.take().len(), 3);
+
+        let changes = ScriptedChanges::new([Err(Timeout), Err(Disconnected)]);
+        assert!(!wait_for_changes(&changes));
+        assert_eq!(changes.timeouts.take().len(), 2);
+    }
+
+    #[test]
+    fn skip_ignored_changes() -> Result<()> {
+        let dir = TempDir::new("watch")?;
+        let root = &dir.0;
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join(GITIGNORE), "*.log\n/generated/\n")?;
+        let filter = ChangesFilter {
+            target_directory: root.join("target"),
+            gitignores: vec![Gitignore::new(root.join(GITIGNORE)).0],
+        };
+        let event = |kind, path: &str| Event::new(kind).add_path(root.join(path));
+        let modified = EventKind::Modify(ModifyKind::Any);
+
+        let relevant = [
+            "src/main.rs",
+            "Cargo.toml",
+            "target/debug/app",
+            "src/debug.log",
+            "generated/mod.rs",
+            "src/generated/mod.rs",
+            ".git/index",
+        ]
+        .into_iter()
+        .filter(|path| filter.is_relevant(&event(modified, path)))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            relevant,
+            ["src/main.rs", "Cargo.toml", "src/generated/mod.rs"]
+        );
+
+        assert!(filter.is_relevant(&event(EventKind::Create(CreateKind::File), "src/lib.rs")));
+        assert!(!filter.is_relevant(&event(EventKind::Access(AccessKind::Any), "src/main.rs")));
+        assert!(!filter.is_relevant(&Event::new(modified)));
+        Ok(())
+    }
+}
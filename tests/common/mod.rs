@@ -0,0 +1,41 @@
+// Each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use anyhow::{Context, Result};
+use cargo_limit::process::CARGO_EXECUTABLE;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+pub fn stub_dir(project: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/stubs")
+        .join(project)
+}
+
+pub fn target_dir() -> Result<PathBuf> {
+    Ok(env::current_exe()?
+        .parent()
+        .context("parent")?
+        .join("../../release"))
+}
+
+pub fn cleanup(project_dir: &Path) -> Result<()> {
+    let _ = Command::new(CARGO_EXECUTABLE)
+        .args(["clean"])
+        .current_dir(project_dir)
+        .output()?;
+    Ok(())
+}
+
+pub fn resolve_dependency(bin: &str, target_dir: &Path) -> Result<PathBuf> {
+    // file-locked by cargo, no need in mutex
+    let bin_path = target_dir.join(bin);
+    let output = Command::new(CARGO_EXECUTABLE)
+        .args(["build", "--release", "--bin", bin])
+        .output()?;
+    assert!(output.status.success());
+    Ok(bin_path)
+}
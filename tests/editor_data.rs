@@ -1,9 +1,12 @@
+mod common;
+
 use anyhow::{Context, Result};
 use cargo_limit::{env_vars, models::EditorData, process::CARGO_EXECUTABLE};
 use cargo_metadata::diagnostic::DiagnosticLevel;
+use common::{cleanup, resolve_dependency, stub_dir, target_dir};
 use std::{
     collections::HashSet,
-    env, fs,
+    fs,
     path::{Path, PathBuf},
     process::Command,
     sync::{Mutex, OnceLock},
@@ -23,6 +26,12 @@ fn a() -> Result<()> {
     let data = check("a")?;
     assert_count(&data, DiagnosticLevel::Warning, 0);
     assert_count(&data, DiagnosticLevel::Error, 4);
+    assert!(
+        data.locations
+            .iter()
+            .flat_map(|i| &i.related)
+            .any(|i| i.label.is_some())
+    );
     Ok(())
 }
 
@@ -90,14 +99,10 @@ fn check(project: &str) -> Result<EditorData> {
 }
 
 fn check_with(bin: &str, args: &[&str], project: &str, warnings: Warnings) -> Result<EditorData> {
-    let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR"));
-    let project_dir = workspace_root.join("tests/stubs").join(project);
+    let project_dir = stub_dir(project);
     cleanup(&project_dir)?;
 
-    let target_dir = env::current_exe()?
-        .parent()
-        .context("parent")?
-        .join("../../release");
+    let target_dir = target_dir()?;
     let bin_path = resolve_dependency(bin, &target_dir)?;
 
     let output = Command::new(bin_path)
@@ -105,6 +110,8 @@ fn check_with(bin: &str, args: &[&str], project: &str, warnings: Warnings) -> Re
         .env(env_vars::RUSTFLAGS, "")
         .env(env_vars::EDITOR, resolve_jq(&target_dir)?)
         .env(env_vars::TIME_LIMIT, "0")
+        .env_remove(env_vars::GITHUB_ACTIONS)
+        .env_remove(env_vars::GITLAB_CI)
         .env(env_vars::FORCE_WARN, warnings.force.to_string().as_str())
         .env(
             env_vars::DEPS_WARN,
@@ -200,14 +207,6 @@ fn count_path_dependencies_warnings(project: &str, warnings: Warnings) -> Result
     Ok(result)
 }
 
-fn cleanup(project_dir: &Path) -> Result<()> {
-    let _ = Command::new(CARGO_EXECUTABLE)
-        .args(["clean"])
-        .current_dir(&project_dir)
-        .output()?;
-    Ok(())
-}
-
 fn assert_count(data: &EditorData, level: DiagnosticLevel, count: usize) {
     assert_eq!(
         data.locations.iter().filter(|i| i.level == level).count(),
@@ -241,13 +240,3 @@ fn resolve_jq(target_dir: &Path) -> Result<PathBuf> {
     }
     Ok(bin_path)
 }
-
-fn resolve_dependency(bin: &str, target_dir: &Path) -> Result<PathBuf> {
-    // file-locked by cargo, no need in mutex
-    let bin_path = target_dir.join(bin);
-    let output = Command::new(CARGO_EXECUTABLE)
-        .args(["build", "--release", "--bin", bin])
-        .output()?;
-    assert!(output.status.success());
-    Ok(bin_path)
-}
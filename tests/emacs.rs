@@ -0,0 +1,85 @@
+#![cfg(unix)]
+
+mod common;
+
+use anyhow::Result;
+use cargo_limit::models::PROTOCOL_VERSION;
+use common::{resolve_dependency, target_dir};
+use serde_json::json;
+use std::{
+    env, fs,
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn locations_are_sent_to_emacsclient() -> Result<()> {
+    let bin_path = resolve_dependency("_cargo-limit-open-in-emacs", &target_dir()?)?;
+
+    // Records its arguments, separated by NUL
+    let fake_bin_dir = env::temp_dir().join(format!("cargo-limit-emacs-{}", std::process::id()));
+    fs::create_dir_all(&fake_bin_dir)?;
+    let args_path = fake_bin_dir.join("args");
+    let emacsclient_path = fake_bin_dir.join("emacsclient");
+    fs::write(
+        &emacsclient_path,
+        format!(
+            "#!/bin/sh\nprintf '%s\\0' \"$@\" > '{}'\n",
+            args_path.display()
+        ),
+    )?;
+    fs::set_permissions(&emacsclient_path, fs::Permissions::from_mode(0o755))?;
+
+    let editor_data = json!({
+        "protocol_version": PROTOCOL_VERSION,
+        "workspace_root": "/work/space",
+        "locations": [
+            {
+                "path": "/work/space/src/main.rs",
+                "line": 6,
+                "column": 5,
+                "message": "cannot find value `x` in this scope",
+                "level": "error",
+            },
+            {
+                "path": "/work/space/src/lib.rs",
+                "line": 1,
+                "column": 1,
+                "message": "unused \"import\" \\ here\nsecond line",
+                "level": "warning",
+            },
+        ],
+        "corrected_locations": 0,
+    });
+    let path = format!(
+        "{}:{}",
+        fake_bin_dir.display(),
+        env::var("PATH").unwrap_or_default()
+    );
+    let mut child = Command::new(bin_path)
+        .env("PATH", path)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(editor_data.to_string().as_bytes())?;
+    assert!(child.wait()?.success());
+
+    let args = fs::read_to_string(&args_path)?;
+    let args = args.split_terminator('\0').collect::<Vec<_>>();
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[0], "--eval");
+    let expr = args[1];
+    assert!(expr.contains(r#"(get-buffer-create "*cargo-limit: /work/space*")"#));
+    assert!(expr.contains(r#"(setq default-directory "/work/space/")"#));
+    assert!(expr.contains(
+        "(insert \"src/main.rs:6:5: error: cannot find value `x` in this scope\nsrc/lib.rs:1:1: \
+         warning: unused \\\"import\\\" \\\\ here\n  second line\n\")"
+    ));
+
+    fs::remove_dir_all(&fake_bin_dir)?;
+    Ok(())
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use anyhow::{Context, Result};
+use cargo_limit::env_vars;
+use common::{cleanup, resolve_dependency, stub_dir, target_dir};
+use serde_json::{Value, json};
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+const INITIALIZE_ID: u64 = 1;
+const SHUTDOWN_ID: u64 = 2;
+
+#[test]
+fn diagnostics_are_published_on_save() -> Result<()> {
+    let project_dir = stub_dir("a");
+    cleanup(&project_dir)?;
+
+    let mut server = Server::spawn(&[])?;
+    let root_uri = server.initialize(&project_dir)?;
+    server.did_save(&root_uri)?;
+    server.send(json!({ "jsonrpc": "2.0", "id": SHUTDOWN_ID, "method": "shutdown" }))?;
+
+    let mut diagnostics = Vec::new();
+    loop {
+        let message = server.receive()?;
+        if message["id"] == SHUTDOWN_ID {
+            break;
+        }
+        assert_eq!(message["method"], "textDocument/publishDiagnostics");
+        let uri = message["params"]["uri"].as_str().context("uri")?;
+        assert!(uri.starts_with(&root_uri));
+        diagnostics.extend(
+            message["params"]["diagnostics"]
+                .as_array()
+                .context("diagnostics")?
+                .clone(),
+        );
+    }
+    assert!(server.exit()?);
+    cleanup(&project_dir)?;
+
+    assert_eq!(diagnostics.len(), 4);
+    assert!(diagnostics.iter().all(|i| i["severity"] == 1));
+    Ok(())
+}
+
+#[test]
+fn errors_are_logged_and_server_keeps_running() -> Result<()> {
+    let project_dir = stub_dir("a");
+
+    let mut server = Server::spawn(&["--limit-messages=many"])?;
+    server.header_name = "content-length";
+    let root_uri = server.initialize(&project_dir)?;
+    server.did_save(&root_uri)?;
+
+    let message = server.receive()?;
+    assert_eq!(message["method"], "window/logMessage");
+    assert_eq!(message["params"]["type"], 1);
+    let text = message["params"]["message"].as_str().context("message")?;
+    assert!(text.contains("--limit-messages"), "{text}");
+
+    server.send(json!({ "jsonrpc": "2.0", "id": SHUTDOWN_ID, "method": "shutdown" }))?;
+    assert_eq!(server.receive()?["id"], SHUTDOWN_ID);
+    assert!(server.exit()?);
+    Ok(())
+}
+
+#[test]
+fn reports_are_not_written_on_save() -> Result<()> {
+    let project_dir = stub_dir("a");
+    let reports_dir = env::temp_dir().join(format!("cargo-limit-lsp-{}", std::process::id()));
+    fs::create_dir_all(&reports_dir)?;
+    let checkstyle_report = reports_dir.join("checkstyle.xml");
+    let junit_report = reports_dir.join("junit.xml");
+
+    let mut server = Server::spawn(&[
+        &format!("--limit-checkstyle-report={}", checkstyle_report.display()),
+        &format!("--limit-junit-report={}", junit_report.display()),
+    ])?;
+    let root_uri = server.initialize(&project_dir)?;
+    server.did_save(&root_uri)?;
+    server.send(json!({ "jsonrpc": "2.0", "id": SHUTDOWN_ID, "method": "shutdown" }))?;
+    while server.receive()?["id"] != SHUTDOWN_ID {}
+    assert!(server.exit()?);
+
+    assert!(!checkstyle_report.exists());
+    assert!(!junit_report.exists());
+    fs::remove_dir_all(&reports_dir)?;
+    Ok(())
+}
+
+struct Server {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    header_name: &'static str,
+}
+
+impl Server {
+    fn spawn(args: &[&str]) -> Result<Self> {
+        let bin_path = resolve_dependency("cargo-limit-lsp", &target_dir()?)?;
+        let mut child = Command::new(bin_path)
+            .args(args)
+            .env(env_vars::RUSTFLAGS, "")
+            .env(env_vars::TIME_LIMIT, "0")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().context("stdin")?;
+        let stdout = BufReader::new(child.stdout.take().context("stdout")?);
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            header_name: "Content-Length",
+        })
+    }
+
+    fn initialize(&mut self, project_dir: &Path) -> Result<String> {
+        let root_uri = format!("file://{}", project_dir.to_string_lossy());
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": INITIALIZE_ID,
+            "method": "initialize",
+            "params": { "rootUri": root_uri, "capabilities": {} },
+        }))?;
+        let response = self.receive()?;
+        assert_eq!(response["id"], INITIALIZE_ID);
+        assert!(response["result"]["capabilities"]["textDocumentSync"]["save"].is_object());
+        Ok(root_uri)
+    }
+
+    fn did_save(&mut self, root_uri: &str) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didSave",
+            "params": { "textDocument": { "uri": format!("{root_uri}/src/lib.rs") } },
+        }))
+    }
+
+    fn exit(mut self) -> Result<bool> {
+        self.send(json!({ "jsonrpc": "2.0", "method": "exit" }))?;
+        Ok(self.child.wait()?.success())
+    }
+
+    fn send(&mut self, message: Value) -> Result<()> {
+        let body = serde_json::to_string(&message)?;
+        write!(
+            self.stdin,
+            "{}: {}\r\n\r\n{body}",
+            self.header_name,
+            body.len()
+        )?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Value> {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            self.stdout.read_line(&mut header)?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            } else if let Some(length) = header.strip_prefix("Content-Length:") {
+                content_length = Some(length.trim().parse::<usize>()?);
+            }
+        }
+
+        let mut body = vec![0; content_length.context("Content-Length")?];
+        self.stdout.read_exact(&mut body)?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
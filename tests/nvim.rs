@@ -0,0 +1,176 @@
+#![cfg(unix)]
+
+mod common;
+
+use anyhow::Result;
+use cargo_limit::{env_vars, models::PROTOCOL_VERSION};
+use common::{resolve_dependency, target_dir};
+use rmpv::Value;
+use serde_json::json;
+use std::{
+    env, fs,
+    io::{self, Write},
+    os::unix::{fs::PermissionsExt, net::UnixListener},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+const WORKSPACE_ROOT: &str = "/work/space";
+const ESCAPED_WORKSPACE_ROOT: &str = "%work%space";
+
+/// Answers the first request with `error` and returns the called function.
+struct FakeNvim {
+    stopped: Arc<AtomicBool>,
+    server: JoinHandle<Option<String>>,
+}
+
+impl FakeNvim {
+    fn listen(address: &Path, error: Value) -> Result<Self> {
+        let listener = UnixListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = thread::spawn({
+            let stopped = stopped.clone();
+            move || {
+                let mut stream = loop {
+                    match listener.accept() {
+                        Ok((stream, _)) => break stream,
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            if stopped.load(Ordering::Acquire) {
+                                return None;
+                            }
+                            thread::sleep(Duration::from_millis(10));
+                        },
+                        Err(err) => panic!("{err}"),
+                    }
+                };
+                stream.set_nonblocking(false).unwrap();
+                let request = rmpv::decode::read_value(&mut stream).unwrap();
+                let response =
+                    Value::from(vec![Value::from(1), request[1].clone(), error, 0.into()]);
+                rmpv::encode::write_value(&mut stream, &response).unwrap();
+                request[3][0].as_str().map(ToOwned::to_owned)
+            }
+        });
+        Ok(Self { stopped, server })
+    }
+
+    fn called_function(self) -> Option<String> {
+        self.stopped.store(true, Ordering::Release);
+        self.server.join().unwrap()
+    }
+}
+
+struct Dirs {
+    runtime_dir: PathBuf,
+    parent_address: PathBuf,
+    instance_address: PathBuf,
+}
+
+impl Dirs {
+    fn new(name: &str) -> Result<Self> {
+        let runtime_dir =
+            env::temp_dir().join(format!("cargo-limit-nvim-{name}-{}", std::process::id()));
+        let sockets_dir = runtime_dir.join("cargo-limit");
+        fs::create_dir_all(&sockets_dir)?;
+        fs::set_permissions(&sockets_dir, fs::Permissions::from_mode(0o700))?;
+        Ok(Self {
+            parent_address: runtime_dir.join("parent"),
+            instance_address: sockets_dir.join(format!("{ESCAPED_WORKSPACE_ROOT}-123")),
+            runtime_dir,
+        })
+    }
+
+    fn open_in_nvim(&self) -> Result<Output> {
+        let bin_path = resolve_dependency("_cargo-limit-open-in-nvim", &target_dir()?)?;
+        let editor_data = json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "workspace_root": WORKSPACE_ROOT,
+            "locations": [],
+            "corrected_locations": 0,
+        });
+        let mut child = Command::new(bin_path)
+            .env(env_vars::NVIM, &self.parent_address)
+            .env(env_vars::XDG_RUNTIME_DIR, &self.runtime_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(editor_data.to_string().as_bytes())?;
+        Ok(child.wait_with_output()?)
+    }
+}
+
+impl Drop for Dirs {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.runtime_dir);
+    }
+}
+
+#[test]
+fn parent_nvim_is_updated() -> Result<()> {
+    let dirs = Dirs::new("parent")?;
+    let parent = FakeNvim::listen(&dirs.parent_address, Value::Nil)?;
+    let instance = FakeNvim::listen(&dirs.instance_address, Value::Nil)?;
+
+    let output = dirs.open_in_nvim()?;
+    assert!(output.status.success());
+    assert_eq!(
+        parent.called_function().as_deref(),
+        Some("g:CargoLimitOpen")
+    );
+    assert_eq!(instance.called_function(), None);
+    Ok(())
+}
+
+#[test]
+fn workspace_instances_are_updated_if_parent_nvim_fails() -> Result<()> {
+    let dirs = Dirs::new("fallback")?;
+    let error = Value::from(vec![Value::from(0), "E117: Unknown function".into()]);
+    let parent = FakeNvim::listen(&dirs.parent_address, error)?;
+    let instance = FakeNvim::listen(&dirs.instance_address, Value::Nil)?;
+
+    let output = dirs.open_in_nvim()?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!(
+            "{}: nvim error: E117: Unknown function\n",
+            dirs.parent_address.display()
+        )
+    );
+    assert_eq!(
+        parent.called_function().as_deref(),
+        Some("g:CargoLimitOpen")
+    );
+    assert_eq!(
+        instance.called_function().as_deref(),
+        Some("g:CargoLimitOpen")
+    );
+    Ok(())
+}
+
+#[test]
+fn workspace_instances_are_updated_if_parent_nvim_is_gone() -> Result<()> {
+    let dirs = Dirs::new("gone")?;
+    let instance = FakeNvim::listen(&dirs.instance_address, Value::Nil)?;
+
+    let output = dirs.open_in_nvim()?;
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    assert_eq!(
+        instance.called_function().as_deref(),
+        Some("g:CargoLimitOpen")
+    );
+    Ok(())
+}
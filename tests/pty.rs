@@ -0,0 +1,37 @@
+#![cfg(unix)]
+
+mod common;
+
+use anyhow::Result;
+use cargo_limit::{env_vars, pty::Pty};
+use common::{cleanup, resolve_dependency, stub_dir, target_dir};
+use std::{io::Read, process::Command};
+
+#[test]
+fn custom_test_runner_detects_terminal() -> Result<()> {
+    let project_dir = stub_dir("custom_test_runner");
+    cleanup(&project_dir)?;
+
+    let bin_path = resolve_dependency("cargo-ltest", &target_dir()?)?;
+    let Pty { mut master, slave } = Pty::open(&std::io::stdin())?;
+    let mut command = Command::new(bin_path);
+    command
+        .env(env_vars::RUSTFLAGS, "")
+        .env(env_vars::EDITOR, "")
+        .env(env_vars::TIME_LIMIT, "0")
+        .env_remove(env_vars::GITHUB_ACTIONS)
+        .env_remove(env_vars::GITLAB_CI)
+        .stdout(slave)
+        .current_dir(&project_dir);
+    let mut child = command.spawn()?;
+    drop(command);
+
+    let mut output = String::new();
+    master.read_to_string(&mut output)?;
+    let exit_status = child.wait()?;
+    cleanup(&project_dir)?;
+
+    assert!(exit_status.success());
+    assert!(output.contains("stdout is terminal: true\n"), "{output:?}");
+    Ok(())
+}
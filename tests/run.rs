@@ -0,0 +1,128 @@
+#![cfg(unix)]
+
+mod common;
+
+use anyhow::{Context, Result};
+use cargo_limit::{
+    env_vars,
+    models::{EditorData, Level},
+};
+use common::{cleanup, resolve_dependency, stub_dir, target_dir};
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+const LINE_TIMEOUT: Duration = Duration::from_secs(120);
+
+struct Editor {
+    dir: PathBuf,
+}
+
+impl Editor {
+    // Appends every received editor data into a file
+    fn new() -> Result<Self> {
+        let dir = env::temp_dir().join(format!("cargo-limit-run-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let script = dir.join("editor");
+        fs::write(
+            &script,
+            "#!/bin/sh\ncat >> \"$0.log\"\necho >> \"$0.log\"\n",
+        )?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+        Ok(Self { dir })
+    }
+
+    fn script(&self) -> PathBuf {
+        self.dir.join("editor")
+    }
+
+    fn received(&self) -> Result<Vec<EditorData>> {
+        let log = fs::read_to_string(self.dir.join("editor.log")).unwrap_or_default();
+        Ok(serde_json::Deserializer::from_str(&log)
+            .into_iter()
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+impl Drop for Editor {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn app_output_is_forwarded_while_it_runs() -> Result<()> {
+    let project_dir = stub_dir("interleaved_output");
+    cleanup(&project_dir)?;
+    let editor = Editor::new()?;
+
+    // Both streams go into one pipe, so that their order can be checked
+    let (reader, writer) = io::pipe()?;
+    let bin_path = resolve_dependency("cargo-llrun", &target_dir()?)?;
+    let mut child = Command::new(bin_path)
+        .env(env_vars::RUSTFLAGS, "")
+        .env(env_vars::EDITOR, editor.script())
+        .env(env_vars::TIME_LIMIT, "0")
+        .env_remove(env_vars::GITHUB_ACTIONS)
+        .env_remove(env_vars::GITLAB_CI)
+        .stdin(Stdio::piped())
+        .stdout(writer.try_clone()?)
+        .stderr(writer)
+        .current_dir(&project_dir)
+        .spawn()?;
+    let mut stdin = child.stdin.take().context("stdin")?;
+
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let _ = sender.send(line);
+        }
+    });
+
+    // The app waits for input after each line, so the next one is written
+    // only after the previous one is forwarded
+    let mut app_lines = Vec::new();
+    let mut editor_data_before_exit = None;
+    while app_lines.len() < 4 {
+        let line = lines.recv_timeout(LINE_TIMEOUT)??;
+        if line.starts_with("stdout ") || line.starts_with("stderr ") {
+            editor_data_before_exit.get_or_insert(editor.received()?);
+            app_lines.push(line);
+            writeln!(stdin)?;
+        }
+    }
+    let output = lines.into_iter().collect::<Result<Vec<_>, _>>()?;
+    assert!(!child.wait()?.success());
+    cleanup(&project_dir)?;
+
+    assert_eq!(app_lines, ["stdout 0", "stderr 0", "stdout 1", "stderr 1"]);
+    assert!(output.iter().any(|line| line.contains("panicked")));
+
+    // Compiler warning is sent before the app exits, then it's sent again with the panic
+    let editor_data_before_exit = editor_data_before_exit.context("no app output")?;
+    assert_eq!(levels(&editor_data_before_exit), [vec![Level::Warning]]);
+    let editor_data = editor.received()?;
+    assert_eq!(
+        levels(&editor_data),
+        [vec![Level::Warning], vec![Level::Error, Level::Warning]]
+    );
+    assert_eq!(
+        editor_data[1].locations[0].path,
+        Path::new(&project_dir).join("src/main.rs")
+    );
+    Ok(())
+}
+
+fn levels(editor_data: &[EditorData]) -> Vec<Vec<Level>> {
+    editor_data
+        .iter()
+        .map(|i| i.locations.iter().map(|location| location.level).collect())
+        .collect()
+}
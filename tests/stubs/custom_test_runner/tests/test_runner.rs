@@ -0,0 +1,5 @@
+use std::io::{self, IsTerminal};
+
+fn main() {
+    println!("stdout is terminal: {}", io::stdout().is_terminal());
+}
@@ -0,0 +1,13 @@
+use std::io::{self, BufRead};
+
+fn main() {
+    let unused = 0;
+    let mut lines = io::stdin().lock().lines();
+    for i in 0..2 {
+        println!("stdout {i}");
+        lines.next();
+        eprintln!("stderr {i}");
+        lines.next();
+    }
+    panic!("done");
+}